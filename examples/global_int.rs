@@ -1,3 +1,5 @@
+use std::ptr::addr_of_mut;
+
 #[no_mangle]
 static mut SOME_INT: i32 = 15;
 
@@ -8,8 +10,10 @@ memflex::global! {
 
 fn main() {
     unsafe {
-        assert_eq!(SOME_INT, *GLOBAL_INT);
-        SOME_INT += 10;
-        assert_eq!(SOME_INT, *GLOBAL_INT);
+        let some_int = addr_of_mut!(SOME_INT);
+
+        assert_eq!(*some_int, *GLOBAL_INT);
+        *some_int += 10;
+        assert_eq!(*some_int, *GLOBAL_INT);
     }
 }
@@ -19,6 +19,33 @@ pub enum MfError {
     InvalidString,
     /// Process has died and is no longer available
     ProcessDied,
+    /// Pattern string is malformed, contains the byte position of the offending character
+    InvalidPattern(usize),
+    /// Memory at the address can't be read
+    InvalidAddress(usize),
+    /// Address expression is malformed, contains the byte position of the offending character
+    InvalidExpression(usize),
+    /// Terminator wasn't found within the maximum length
+    TerminatorNotFound,
+    /// Memory at the address doesn't contain the expected bytes
+    UnexpectedBytes(usize),
+    /// Patch overlaps another one, contains the address of the other patch
+    PatchOverlap(usize),
+    /// Instruction at the address can't be decoded or relocated
+    InvalidInstruction(usize),
+    /// No free memory within 2GB of the address
+    NoMemoryNearby(usize),
+    /// Module doesn't import the symbol
+    ImportNotFound,
+    /// Value is empty or doesn't fit the kind or the condition of a value scan
+    InvalidValue,
+    /// Pointer of a chain couldn't be read
+    InvalidPointer {
+        /// Level of the pointer, starting from zero for the innermost one
+        level: usize,
+        /// Address of the pointer
+        address: usize,
+    },
 }
 
 #[allow(dead_code)]
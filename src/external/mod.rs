@@ -8,7 +8,13 @@ mod unix;
 #[cfg(unix)]
 pub use unix::*;
 
-use crate::types::Protection;
+mod scan;
+pub(crate) use scan::*;
+
+use crate::{
+    make_signature, types::ModuleInfoWithName, Image, Match, Matcher, MemorySink, MemorySource,
+    MfError, PatternSet, ScanOptions, Signature, SignatureChecker, SignatureReport,
+};
 
 #[derive(Debug)]
 /// Single process
@@ -60,13 +66,146 @@ impl ProcessEntry {
     }
 }
 
-/// Represents a chunk of mapped memory in a process
-#[derive(Debug)]
-pub struct MemoryRegion {
-    /// Start
-    pub from: usize,
-    /// End
-    pub to: usize,
-    /// Prtection
-    pub prot: Protection,
+pub use crate::types::MemoryRegion;
+
+impl MemorySource for OwnedProcess {
+    #[inline]
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        OwnedProcess::read_buf(self, address, buf)
+    }
+
+    #[inline]
+    fn maps(&self) -> crate::Result<Vec<MemoryRegion>> {
+        OwnedProcess::maps(self)
+    }
+
+    #[inline]
+    fn modules(&self) -> crate::Result<Vec<ModuleInfoWithName>> {
+        Ok(OwnedProcess::modules(self)?.collect())
+    }
+}
+
+impl MemorySink for OwnedProcess {
+    #[inline]
+    fn write_buf(&self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        OwnedProcess::write_buf(self, address, buf)
+    }
+}
+
+impl OwnedProcess {
+    /// Finds all occurences of the pattern in a given range, returning matches with their capture slots.
+    pub fn find_matches<'a>(
+        &'a self,
+        pat: impl Matcher + 'a,
+        start: usize,
+        len: usize,
+    ) -> impl Iterator<Item = Match> + 'a {
+        let m = Match::new(&pat, 0);
+        self.find_pattern(pat, start, len).map(move |a| m.at(a))
+    }
+
+    /// Finds all occurences of the pattern in the specified module,
+    /// returning matches with their capture slots.
+    pub fn find_matches_in_module<'a>(
+        &'a self,
+        pat: impl Matcher + 'a,
+        module_name: &str,
+    ) -> crate::Result<impl Iterator<Item = Match> + 'a> {
+        let m = Match::new(&pat, 0);
+        Ok(self
+            .find_pattern_in_module(pat, module_name)?
+            .map(move |a| m.at(a)))
+    }
+
+    /// Finds occurences of the pattern in a given range,
+    /// with control over direction, amount and alignment of the matches.
+    /// # Behavior
+    /// Memory is read as described in [scanning](OwnedProcess#scanning),
+    /// in reverse chunks are read starting from the end of the range.
+    pub fn find_pattern_with<'a>(
+        &'a self,
+        pat: impl Matcher + 'a,
+        start: usize,
+        len: usize,
+        options: ScanOptions,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let read = |address, buf: &mut [u8]| self.read_buf(address, buf);
+        RemoteScan::new(pat, read, start, len, options).take(options.limit())
+    }
+
+    /// Searches for every pattern of the set in a given range in a single pass,
+    /// returning ids of the patterns with their matches.
+    /// # Behavior
+    /// Memory is read as described in [scanning](OwnedProcess#scanning).
+    pub fn find_pattern_set<'a>(
+        &'a self,
+        set: &'a PatternSet,
+        start: usize,
+        len: usize,
+    ) -> impl Iterator<Item = (usize, Match)> + 'a {
+        RemoteSetScan::new(set, |address, buf| self.read_buf(address, buf), start, len)
+    }
+
+    /// Searches for every pattern of the set in the specified module in a single pass,
+    /// returning ids of the patterns with their matches.
+    pub fn find_pattern_set_in_module<'a>(
+        &'a self,
+        set: &'a PatternSet,
+        module_name: &str,
+    ) -> crate::Result<impl Iterator<Item = (usize, Match)> + 'a> {
+        let module = self.find_module(module_name)?;
+        Ok(self.find_pattern_set(set, module.base as _, module.size))
+    }
+
+    /// Checks every pattern of the checker against a module of the process,
+    /// e.g. one returned by [`OwnedProcess::find_module`].
+    /// # Behavior
+    /// Memory is read as described in [scanning](OwnedProcess#scanning).
+    pub fn check_signatures(
+        &self,
+        checker: &SignatureChecker,
+        module: &ModuleInfoWithName,
+    ) -> SignatureReport {
+        let base = module.base as usize;
+        let found = self.find_pattern_set(checker.patterns(), base, module.size);
+        checker.report_matches(base, found)
+    }
+
+    /// Creates a signature for the code at `address`, growing it until it is unique
+    /// within the module that contains the address.
+    /// # Behavior
+    /// The module is read once, pages that can't be read are treated as zeroes.
+    /// Relative branch targets, RIP-relative displacements and operands
+    /// that point into the module are wildcarded.
+    /// The pattern won't be longer than `max_len` bytes.
+    pub fn make_signature(&self, address: usize, max_len: usize) -> crate::Result<Signature> {
+        let module = self
+            .modules()?
+            .find(|m| (m.base as usize..m.base as usize + m.size).contains(&address))
+            .ok_or(MfError::ModuleNotFound)?;
+
+        let range = module.base as usize..module.base as usize + module.size;
+        let data = read_image(|a, buf| self.read_buf(a, buf), range.start, module.size);
+        let image = Image {
+            segments: vec![(range.start, &data[..])],
+            range,
+        };
+
+        make_signature(&image, address, max_len).ok_or(MfError::ModuleNotFound)
+    }
+
+    /// Reads value of type `T` at the capture slot of the match.
+    /// # Panics
+    /// If the pattern has less than `slot + 1` capture slots.
+    pub fn read_capture<T>(&self, m: &Match, slot: usize) -> crate::Result<T> {
+        self.read(m.capture(slot))
+    }
+
+    /// Reads `i32` displacement at the capture slot and resolves it relative to the end of
+    /// the instruction, `insn_end` being its offset from the start of the match.
+    /// # Panics
+    /// If the pattern has less than `slot + 1` capture slots.
+    pub fn rip_relative(&self, m: &Match, slot: usize, insn_end: usize) -> crate::Result<usize> {
+        Ok(m.resolve_rip_relative(self.read_capture(m, slot)?, insn_end))
+    }
 }
@@ -0,0 +1,315 @@
+use crate::{
+    read_available, read_ranges, Automaton, Match, Matcher, PatternSet, ScanOptions, Scanner,
+    SetScan, CHUNK_SIZE, PAGE_SIZE,
+};
+use std::vec;
+
+/// Reads `buf.len()` bytes at `address`, returning amount of contiguous bytes
+/// that could be read at the end of `buf`.
+/// Falls back to reading the last page if the whole buffer can't be read at once.
+fn read_available_rev(
+    read: &mut impl FnMut(usize, &mut [u8]) -> crate::Result<usize>,
+    address: usize,
+    buf: &mut [u8],
+) -> usize {
+    match read(address, buf) {
+        Ok(n) if n == buf.len() => n,
+        _ => {
+            let page = ((address + buf.len() - 1) / PAGE_SIZE * PAGE_SIZE).max(address);
+            let buf = &mut buf[page - address..];
+            match read(page, buf) {
+                Ok(n) if n == buf.len() => n,
+                _ => 0,
+            }
+        }
+    }
+}
+
+/// Reads `len` bytes at `start` in chunks, leaving pages that can't be read zeroed.
+pub(crate) fn read_image(
+    mut read: impl FnMut(usize, &mut [u8]) -> crate::Result<usize>,
+    start: usize,
+    len: usize,
+) -> Vec<u8> {
+    let mut image = vec![0; len];
+    read_ranges(&mut read, start, &mut image);
+    image
+}
+
+/// Reads memory of another process in page aligned chunks.
+/// # Behavior
+/// Consecutive chunks overlap by `overlap` bytes if they are contiguous,
+/// pages that can't be read are skipped.
+/// When contiguous memory ends, its last `overlap` bytes are yielded once more as a chunk
+/// of their own that is marked as the last one.
+///
+/// In reverse, chunks are read from the end of the range and the first `overlap` bytes
+/// of the previous chunk are appended to the current one instead.
+pub(crate) struct Chunks<R> {
+    read: R,
+    buf: Vec<u8>,
+    /// Offset of the current chunk in `buf`.
+    offset: usize,
+    /// Address of the first byte of the current chunk.
+    base: usize,
+    /// Amount of valid bytes in the current chunk.
+    filled: usize,
+    /// Amount of bytes carried over from the previous chunk.
+    kept: usize,
+    /// Memory doesn't continue after the current chunk.
+    last: bool,
+    /// Address of the next chunk to read, or of the end of it in reverse.
+    cursor: usize,
+    start: usize,
+    end: usize,
+    reverse: bool,
+}
+
+impl<R> Chunks<R>
+where
+    R: FnMut(usize, &mut [u8]) -> crate::Result<usize>,
+{
+    pub fn new(read: R, start: usize, len: usize, overlap: usize, reverse: bool) -> Self {
+        let end = start.saturating_add(len);
+        let cursor = if reverse { end } else { start };
+        Self {
+            read,
+            buf: vec![0; CHUNK_SIZE + overlap],
+            offset: 0,
+            base: cursor,
+            filled: 0,
+            kept: 0,
+            last: false,
+            cursor,
+            start,
+            end,
+            reverse,
+        }
+    }
+
+    /// Reads the next chunk, keeping the tail of the previous one if it's contiguous.
+    /// Returns `false` when the range is exhausted.
+    pub fn advance(&mut self) -> bool {
+        if self.reverse {
+            return self.advance_rev();
+        }
+        if self.cursor >= self.end {
+            return false;
+        }
+
+        let keep = if self.base + self.filled == self.cursor && !self.last {
+            self.filled.min(self.buf.len() - CHUNK_SIZE)
+        } else {
+            0
+        };
+        self.buf.copy_within(self.filled - keep..self.filled, 0);
+
+        let chunk_end = ((self.cursor / CHUNK_SIZE + 1) * CHUNK_SIZE).min(self.end);
+        let want = chunk_end - self.cursor;
+        let read = read_available(
+            &mut self.read,
+            self.cursor,
+            &mut self.buf[keep..keep + want],
+        );
+
+        if read == 0 {
+            // Tail of the previous chunk is all that's left of the contiguous memory.
+            self.base = self.cursor - keep;
+            self.filled = keep;
+            self.kept = keep;
+            self.last = true;
+            self.cursor = ((self.cursor / PAGE_SIZE + 1) * PAGE_SIZE).min(self.end);
+        } else {
+            self.base = self.cursor - keep;
+            self.filled = keep + read;
+            self.kept = keep;
+            self.cursor += read;
+            self.last = self.cursor >= self.end;
+        }
+
+        true
+    }
+
+    /// Reads the chunk preceding the current one, keeping the head of the current one
+    /// if it's contiguous.
+    fn advance_rev(&mut self) -> bool {
+        if self.cursor <= self.start {
+            return false;
+        }
+
+        let keep = if self.base == self.cursor && self.filled != 0 {
+            self.filled.min(self.buf.len() - CHUNK_SIZE)
+        } else {
+            0
+        };
+
+        let chunk_start = ((self.cursor - 1) / CHUNK_SIZE * CHUNK_SIZE).max(self.start);
+        let want = self.cursor - chunk_start;
+        self.buf.copy_within(self.offset..self.offset + keep, want);
+        let read = read_available_rev(&mut self.read, chunk_start, &mut self.buf[..want]);
+
+        if read == 0 {
+            self.cursor = ((self.cursor - 1) / PAGE_SIZE * PAGE_SIZE).max(self.start);
+            self.offset = 0;
+            self.filled = 0;
+            self.kept = 0;
+            self.last = true;
+        } else {
+            // Memory continues after the chunk as long as something was carried over from
+            // a chunk that wasn't the last one or that didn't fit into the overlap.
+            self.last = keep == 0 || (keep == self.filled && self.last);
+            self.offset = want - read;
+            self.filled = read + keep;
+            self.kept = keep;
+            self.cursor -= read;
+        }
+        self.base = self.cursor;
+
+        true
+    }
+
+    /// Bytes of the current chunk.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.buf[self.offset..self.offset + self.filled]
+    }
+
+    /// Address of the first byte of the current chunk.
+    #[inline]
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Amount of leading bytes of the current chunk that were already part of the previous one,
+    /// or of trailing bytes in reverse.
+    #[inline]
+    pub fn kept(&self) -> usize {
+        self.kept
+    }
+
+    /// Memory doesn't continue after the current chunk.
+    #[inline]
+    pub fn last(&self) -> bool {
+        self.last
+    }
+}
+
+/// Scans memory of another process for a pattern.
+/// # Behavior
+/// Memory is read in chunks that overlap by `pat.len() - 1` bytes.
+/// In reverse, only the offsets that weren't part of the previous chunk are searched.
+pub(crate) struct RemoteScan<M, R> {
+    scanner: Scanner<M>,
+    chunks: Chunks<R>,
+    options: ScanOptions,
+    /// Offsets in the current chunk that are left to search.
+    from: usize,
+    to: usize,
+}
+
+impl<M, R> RemoteScan<M, R>
+where
+    M: Matcher,
+    R: FnMut(usize, &mut [u8]) -> crate::Result<usize>,
+{
+    pub fn new(pat: M, read: R, start: usize, len: usize, options: ScanOptions) -> Self {
+        let overlap = pat.len().saturating_sub(1);
+        Self {
+            chunks: Chunks::new(read, start, len, overlap, options.reverse),
+            scanner: Scanner::new(pat),
+            options,
+            from: 0,
+            to: 0,
+        }
+    }
+}
+
+impl<M, R> Iterator for RemoteScan<M, R>
+where
+    M: Matcher,
+    R: FnMut(usize, &mut [u8]) -> crate::Result<usize>,
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (data, base) = (self.chunks.data(), self.chunks.base());
+            let (last, reverse) = (self.chunks.last(), self.options.reverse);
+            let range = self.from..self.to;
+            let stride = self.options.stride();
+            if let Some(found) = self
+                .scanner
+                .search(data, range, last, reverse, base, stride)
+            {
+                if reverse {
+                    self.to = found;
+                } else {
+                    self.from = found + 1;
+                }
+                return Some(base + found);
+            }
+
+            if !self.chunks.advance() {
+                return None;
+            }
+            self.from = 0;
+            self.to = if reverse {
+                self.chunks.data().len() - self.chunks.kept()
+            } else {
+                usize::MAX
+            };
+        }
+    }
+}
+
+/// Scans memory of another process for every pattern of a set.
+/// # Behavior
+/// Chunks overlap by the length of the longest pattern minus one,
+/// matches that fit entirely into the overlap were reported with the previous chunk.
+pub(crate) struct RemoteSetScan<'a, R> {
+    set: &'a PatternSet,
+    automaton: Automaton,
+    chunks: Chunks<R>,
+    found: vec::IntoIter<(usize, Match)>,
+}
+
+impl<'a, R> RemoteSetScan<'a, R>
+where
+    R: FnMut(usize, &mut [u8]) -> crate::Result<usize>,
+{
+    pub fn new(set: &'a PatternSet, read: R, start: usize, len: usize) -> Self {
+        Self {
+            automaton: Automaton::new(set),
+            chunks: Chunks::new(read, start, len, set.max_len().saturating_sub(1), false),
+            found: vec![].into_iter(),
+            set,
+        }
+    }
+}
+
+impl<R> Iterator for RemoteSetScan<'_, R>
+where
+    R: FnMut(usize, &mut [u8]) -> crate::Result<usize>,
+{
+    type Item = (usize, Match);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(found) = self.found.next() {
+                return Some(found);
+            }
+
+            if !self.chunks.advance() {
+                return None;
+            }
+
+            let (set, base, kept) = (self.set, self.chunks.base(), self.chunks.kept());
+            let (data, last) = (self.chunks.data(), self.chunks.last());
+            self.found = SetScan::new(set, &self.automaton, data, last)
+                .filter(|&(id, offset)| offset + set.get(id).map_or(0, |p| p.len()) > kept)
+                .map(|(id, offset)| (id, set.match_at(id, base + offset)))
+                .collect::<Vec<_>>()
+                .into_iter();
+        }
+    }
+}
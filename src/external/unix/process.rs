@@ -1,7 +1,7 @@
 use crate::{
     external::{MemoryRegion, ProcessEntry},
     types::{ModuleInfoWithName, Protection},
-    Matcher, MfError,
+    Matcher, MfError, ScanOptions,
 };
 use core::{
     mem::{size_of, MaybeUninit},
@@ -17,6 +17,8 @@ use std::{
 /// # Details
 /// There is no such concept as 'owned' procses in unix. (i think).
 /// The name is the same as on windows to reduce the hasle of cross-platform code.
+/// # Scanning
+/// Searches read memory in large chunks, pages that can't be read are skipped.
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct OwnedProcess(pub(crate) u32);
@@ -204,37 +206,15 @@ impl OwnedProcess {
     }
 
     /// Finds all occurences of the pattern in a given range.
-    // TODO: Can be optimized
+    /// # Behavior
+    /// Memory is read as described in [scanning](OwnedProcess#scanning).
     pub fn find_pattern<'a>(
         &'a self,
         pat: impl Matcher + 'a,
         start: usize,
         len: usize,
     ) -> impl Iterator<Item = usize> + 'a {
-        let mut offset = 0;
-        let mut buf = vec![0; pat.len()];
-
-        std::iter::from_fn(move || {
-            loop {
-                if self.read_buf(start + offset, &mut buf[..]).is_err() {
-                    return None;
-                }
-
-                if pat.matches(&buf[..]) {
-                    break;
-                }
-
-                offset += 1;
-
-                if offset >= len {
-                    return None;
-                }
-            }
-
-            offset += 1;
-            Some(start + offset - 1)
-        })
-        .fuse()
+        self.find_pattern_with(pat, start, len, ScanOptions::default())
     }
 
     /// Searches for a pattern in the specified module.
@@ -250,21 +230,7 @@ impl OwnedProcess {
 
     /// Returns an iterator over mapped regions in the process.
     pub fn maps(&self) -> crate::Result<Vec<MemoryRegion>> {
-        Ok(fs::read_to_string(format!("/proc/{}/maps", self.0))
-            .map_err(|_| MfError::ProcessDied)?
-            .lines()
-            .map(|l| {
-                let mut iter = l.split(' ');
-                let (from, to) = iter.next().unwrap().split_once('-').unwrap();
-
-                let from = usize::from_str_radix(from, 16).unwrap();
-                let to = usize::from_str_radix(to, 16).unwrap();
-
-                let prot = Protection::parse(&iter.next().unwrap()[0..3]);
-
-                MemoryRegion { from, to, prot }
-            })
-            .collect())
+        crate::proc_maps(self.0)
     }
 
     /// Queryies protection for the specified address.
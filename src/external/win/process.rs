@@ -2,7 +2,7 @@ use super::{ModuleIterator, OwnedThread, ThreadIterator};
 use crate::{
     external::{MemoryRegion, ProcessEntry},
     types::{ModuleInfoWithName, Protection},
-    Matcher, MfError,
+    Matcher, MfError, ScanOptions,
 };
 use core::mem::{size_of, transmute, zeroed};
 use windows::Win32::{
@@ -22,12 +22,15 @@ use windows::Win32::{
         },
         ProcessStatus::K32GetProcessImageFileNameW,
         Threading::{
-            CreateRemoteThread, GetProcessId, OpenProcess, TerminateProcess, PROCESS_ACCESS_RIGHTS,
+            CreateRemoteThread, GetProcessId, OpenProcess, TerminateProcess,
+            LPTHREAD_START_ROUTINE, PROCESS_ACCESS_RIGHTS,
         },
     },
 };
 
 /// Owned handle to another process
+/// # Scanning
+/// Searches read memory in large chunks, pages that can't be read are skipped.
 #[repr(transparent)]
 pub struct OwnedProcess(HANDLE);
 
@@ -291,7 +294,7 @@ impl OwnedProcess {
                 self.0,
                 None,
                 stack_size.unwrap_or_default(),
-                transmute(start_address),
+                transmute::<usize, LPTHREAD_START_ROUTINE>(start_address),
                 Some(param as _),
                 if suspended { 0x4 } else { 0 },
                 None,
@@ -326,37 +329,15 @@ impl OwnedProcess {
     }
 
     /// Finds all occurences of the pattern in a given range.
-    // @TODO: Can be optimized
+    /// # Behavior
+    /// Memory is read as described in [scanning](OwnedProcess#scanning).
     pub fn find_pattern<'a>(
         &'a self,
         pat: impl Matcher + 'a,
         start: usize,
         len: usize,
     ) -> impl Iterator<Item = usize> + 'a {
-        let mut offset = 0;
-        let mut buf = vec![0; pat.len()];
-
-        std::iter::from_fn(move || {
-            loop {
-                if self.read_buf(start + offset, &mut buf[..]).is_err() {
-                    return None;
-                }
-
-                if pat.matches(&buf[..]) {
-                    break;
-                }
-
-                offset += 1;
-
-                if offset >= len {
-                    return None;
-                }
-            }
-
-            offset += 1;
-            Some(start + offset - 1)
-        })
-        .fuse()
+        self.find_pattern_with(pat, start, len, ScanOptions::default())
     }
 
     /// Finds all occurences of the pattern in the specified module.
@@ -469,7 +450,7 @@ impl Iterator for ProcessIterator {
             let current = ProcessEntry::from(&self.entry);
             self.stop = !Process32NextW(self.h, &mut self.entry).as_bool();
 
-            return current;
+            current
         }
     }
 }
@@ -0,0 +1,146 @@
+use super::CurrentProcess;
+use crate::{Matcher, MemorySource, MfError, Scanner, PAGE_SIZE};
+use core::{
+    mem::{size_of, MaybeUninit},
+    slice::{from_raw_parts, from_raw_parts_mut},
+};
+
+/// Checks that the page containing `address` can be read.
+fn check_readable(address: usize) -> crate::Result<()> {
+    match CurrentProcess.read_buf(address, &mut [0]) {
+        Ok(1) => Ok(()),
+        _ => Err(MfError::InvalidAddress(address)),
+    }
+}
+
+/// Reads a value of type `T` at `address`,
+/// returns [`MfError::InvalidAddress`] instead of faulting if the memory can't be read.
+/// # Safety
+/// Any bit pattern must be a valid `T`, so `bool`, `char`, enums and references can't be read.
+/// ```
+/// # use memflex::{internal::try_read, MfError};
+/// let value = Box::new(1337_u32);
+/// unsafe {
+///     assert_eq!(try_read::<u32>(&*value as *const u32 as usize).unwrap(), 1337);
+///     assert!(matches!(try_read::<u32>(0x10), Err(MfError::InvalidAddress(0x10))));
+/// }
+/// ```
+pub unsafe fn try_read<T>(address: usize) -> crate::Result<T> {
+    let mut value = MaybeUninit::<T>::uninit();
+    let buf = from_raw_parts_mut(value.as_mut_ptr().cast::<u8>(), size_of::<T>());
+
+    match CurrentProcess.read_buf(address, buf) {
+        Ok(n) if n == size_of::<T>() => Ok(value.assume_init()),
+        _ => Err(MfError::InvalidAddress(address)),
+    }
+}
+
+/// Resolves multilevel pointer the same way as [`crate::resolve_multilevel`],
+/// returns [`MfError::InvalidPointer`] with the level and address of the pointer that couldn't be read.
+/// ```
+/// # use memflex::{internal::try_resolve_multilevel, MfError};
+/// let value = Box::new(1337_u32);
+/// let holder = Box::new([0, &*value as *const u32 as usize]);
+///
+/// let v = try_resolve_multilevel::<u32>(holder.as_ptr() as _, &[8, 0]).unwrap();
+/// assert_eq!(unsafe { *v }, 1337);
+/// assert!(matches!(
+///     try_resolve_multilevel::<u32>(0x10 as _, &[8, 0]),
+///     Err(MfError::InvalidPointer { level: 0, address: 0x18 })
+/// ));
+/// ```
+pub fn try_resolve_multilevel<T>(base: *const u8, offsets: &[usize]) -> crate::Result<*const T> {
+    let mut address = base as usize;
+    for (level, &offset) in offsets.iter().enumerate() {
+        address = address.wrapping_add(offset);
+        if level != offsets.len() - 1 {
+            address = unsafe { try_read(address) }
+                .map_err(|_| MfError::InvalidPointer { level, address })?;
+        }
+    }
+
+    Ok(address as _)
+}
+
+/// Creates an inmmutable slice from terminated array, checking every page before reading it.
+/// # Errors
+/// * [`MfError::InvalidAddress`] with the address that couldn't be read.
+/// * [`MfError::TerminatorNotFound`] if none of the first `max_len` items is `last`.
+/// # Safety
+/// * Memory of the array must stay mapped and unchanged for `'a`.
+/// ```
+/// # use memflex::{internal::try_terminated_array, MfError};
+/// let items = b"123\x00";
+/// # unsafe {
+/// assert_eq!(try_terminated_array(items.as_ptr(), 0, 8).unwrap(), b"123");
+/// assert!(matches!(try_terminated_array(items.as_ptr(), 0, 2), Err(MfError::TerminatorNotFound)));
+/// assert!(matches!(try_terminated_array(0x10 as *const u8, 0, 8), Err(MfError::InvalidAddress(0x10))));
+/// # }
+/// ```
+pub unsafe fn try_terminated_array<'a, T: PartialEq>(
+    first: *const T,
+    last: T,
+    max_len: usize,
+) -> crate::Result<&'a [T]> {
+    // End of the memory that is known to be readable.
+    let mut checked = first as usize;
+
+    for len in 0..max_len {
+        let item = first.add(len);
+        while checked < item as usize + size_of::<T>() {
+            check_readable(checked)?;
+            checked = (checked / PAGE_SIZE + 1) * PAGE_SIZE;
+        }
+
+        if *item == last {
+            return Ok(from_raw_parts(first, len));
+        }
+    }
+
+    Err(MfError::TerminatorNotFound)
+}
+
+/// Searches for a pattern by start address and search length, skipping memory that can't be read.
+/// # Behavior
+/// Readable regions are queried once, before the search.
+/// # Safety
+/// * Memory in the range must not be unmapped or protected while the iterator is used.
+pub unsafe fn try_find_pattern(
+    pat: impl Matcher,
+    start: *const u8,
+    len: usize,
+) -> crate::Result<impl Iterator<Item = *const u8>> {
+    let (start, end) = (start as usize, (start as usize).saturating_add(len));
+
+    let mut regions = CurrentProcess.maps()?;
+    regions.retain(|r| r.prot.read() && r.from < end && r.to > start);
+    regions.sort_unstable_by_key(|r| r.from);
+
+    // Contiguous regions are searched as one, so matches can span them.
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for r in regions {
+        let (from, to) = (r.from.max(start), r.to.min(end));
+        match ranges.last_mut() {
+            Some(last) if last.1 == from => last.1 = to,
+            _ => ranges.push((from, to)),
+        }
+    }
+
+    let scanner = Scanner::new(pat);
+    let mut ranges = ranges.into_iter();
+    let mut current = ranges.next();
+    let mut from = 0;
+
+    Ok(core::iter::from_fn(move || loop {
+        let (start, end) = current?;
+        let data = from_raw_parts(start as *const u8, end - start);
+
+        if let Some(found) = scanner.find(data, from) {
+            from = found + 1;
+            return Some((start + found) as *const u8);
+        }
+
+        current = ranges.next();
+        from = 0;
+    }))
+}
@@ -0,0 +1,135 @@
+use super::{unix::find_loaded, unix::LoadedObject, CurrentProcess};
+use crate::{
+    types::{elf::*, ModuleInfo},
+    AppliedPatch, MfError, Patch,
+};
+use core::{ffi::CStr, mem::size_of};
+use std::ffi::CString;
+
+#[cfg(target_arch = "x86_64")]
+const IMPORTS: [u32; 2] = [R_X86_64_GLOB_DAT, R_X86_64_JUMP_SLOT];
+#[cfg(target_arch = "aarch64")]
+const IMPORTS: [u32; 2] = [R_AARCH64_GLOB_DAT, R_AARCH64_JUMP_SLOT];
+
+/// Replaces the address of an imported function in the global offset table of a module,
+/// calls of the function from that module go to the detour.
+/// # Behavior
+/// Every `GLOB_DAT` and `JUMP_SLOT` relocation of the symbol is hooked, so calls through
+/// the PLT and direct calls through the GOT are both redirected. Pages protected by RELRO
+/// are made writable only for the duration of the write.
+///
+/// Original addresses are written back when the hook is dropped.
+/// ```
+/// # use memflex::internal::{find_module_by_name, ImportHook};
+/// extern "C" fn fake_getpid() -> i32 {
+///     1337
+/// }
+///
+/// let exe = std::env::current_exe().unwrap();
+/// let module = find_module_by_name(exe.file_name().unwrap().to_str().unwrap()).unwrap();
+///
+/// let hook = unsafe { ImportHook::new(&module, "getpid", fake_getpid as *const u8) }.unwrap();
+/// assert_eq!(unsafe { libc::getpid() }, 1337);
+/// drop(hook);
+/// assert_ne!(unsafe { libc::getpid() }, 1337);
+/// ```
+pub struct ImportHook {
+    original: usize,
+    slots: Vec<AppliedPatch<'static, CurrentProcess>>,
+}
+
+impl ImportHook {
+    /// Hooks `symbol` imported by `module`.
+    /// # Errors
+    /// * [`MfError::ModuleNotFound`] if `module` doesn't describe a loaded module.
+    /// * [`MfError::ImportNotFound`] if the module doesn't have relocations of the symbol.
+    /// * Errors of [`Patch::apply`].
+    /// # Safety
+    /// * `detour` must be a function with the signature of the imported function.
+    pub unsafe fn new(module: &ModuleInfo, symbol: &str, detour: *const u8) -> crate::Result<Self> {
+        let slots = find_loaded(|o| {
+            let info = o.info();
+            (info.base == module.base && info.size == module.size).then(|| import_slots(o, symbol))
+        })
+        .ok_or(MfError::ModuleNotFound)?;
+
+        let &first = slots.first().ok_or(MfError::ImportNotFound)?;
+        let mut original = (first as *const usize).read();
+        // Slot of a lazily bound import still points to the PLT of the module.
+        let range = module.base as usize..module.base as usize + module.size;
+        if range.contains(&original) {
+            let name = CString::new(symbol).map_err(|_| MfError::ImportNotFound)?;
+            original = libc::dlsym(libc::RTLD_DEFAULT, name.as_ptr()) as usize;
+        }
+
+        let slots = slots
+            .into_iter()
+            .map(|slot| Patch::new(slot, (detour as usize).to_ne_bytes()).apply(&CurrentProcess))
+            .collect::<crate::Result<_>>()?;
+
+        Ok(Self { original, slots })
+    }
+
+    /// Address of the imported function, call it with the signature of the import.
+    #[inline]
+    pub fn original(&self) -> *const u8 {
+        self.original as _
+    }
+
+    /// Addresses of the hooked GOT entries.
+    pub fn slots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.slots.iter().map(|s| s.patch().address)
+    }
+
+    /// Writes the original addresses back now, returning the first error.
+    pub fn restore(self) -> crate::Result<()> {
+        let mut result = Ok(());
+        for slot in self.slots {
+            result = result.and(slot.restore());
+        }
+        result
+    }
+}
+
+/// Addresses of the GOT entries that are relocated to `symbol`.
+fn import_slots(object: &LoadedObject, symbol: &str) -> Vec<usize> {
+    let (mut strtab, mut symtab) = (0, 0);
+    let mut tables = [(0, 0); 2];
+    for entry in object.dynamic() {
+        match entry.tag {
+            DT_STRTAB => strtab = object.dynamic_address(entry.val),
+            DT_SYMTAB => symtab = object.dynamic_address(entry.val),
+            DT_RELA => tables[0].0 = object.dynamic_address(entry.val),
+            DT_RELASZ => tables[0].1 = entry.val as usize,
+            DT_JMPREL => tables[1].0 = object.dynamic_address(entry.val),
+            DT_PLTRELSZ => tables[1].1 = entry.val as usize,
+            _ => {}
+        }
+    }
+    if strtab == 0 || symtab == 0 {
+        return vec![];
+    }
+
+    let mut slots = vec![];
+    for (table, size) in tables.into_iter().filter(|t| t.0 != 0) {
+        for i in 0..size / size_of::<ElfRela>() {
+            let rela = unsafe { *(table as *const ElfRela).add(i) };
+            if !IMPORTS.contains(&rela.kind()) || rela.symbol() == 0 {
+                continue;
+            }
+
+            let name = unsafe {
+                let sym = *(symtab as *const ElfSym).add(rela.symbol());
+                CStr::from_ptr((strtab + sym.name as usize) as _)
+            };
+            if name.to_bytes() == symbol.as_bytes() {
+                slots.push(object.bias() + rela.offset as usize);
+            }
+        }
+    }
+
+    // `DT_RELASZ` can include the `DT_JMPREL` table.
+    slots.sort_unstable();
+    slots.dedup();
+    slots
+}
@@ -0,0 +1,112 @@
+use super::{protect, CurrentProcess};
+use crate::{
+    types::{MemoryRegion, Protection},
+    MemorySource, MfError, PAGE_SIZE,
+};
+
+/// Changes the protection of a range of memory, restoring the original protection
+/// of every affected page when dropped.
+/// # Behavior
+/// The range is extended to whole pages. Pages of mappings with different protections
+/// get their own protection back.
+/// ```
+/// # use memflex::{internal::{allocate, free, ProtectionGuard}, types::Protection};
+/// let page = allocate(None, 0x1000, Protection::R).unwrap();
+/// {
+///     let _guard = ProtectionGuard::new(page as usize + 0x10, 4, Protection::RW).unwrap();
+///     unsafe { page.add(0x10).cast::<u32>().write(1337) };
+/// }
+/// // Page is read-only again
+/// # free(page as usize, 0x1000).unwrap();
+/// ```
+#[must_use]
+#[derive(Debug)]
+pub struct ProtectionGuard {
+    /// Protection of the affected pages before the change, sorted by address.
+    original: Vec<MemoryRegion>,
+}
+
+impl ProtectionGuard {
+    /// Changes the protection of the pages covering `address..address + len` to `prot`.
+    /// # Errors
+    /// * [`MfError::InvalidAddress`] with the first address of the range that isn't mapped.
+    /// * Error of the system call, the original protection is restored in that case.
+    pub fn new(address: usize, len: usize, prot: Protection) -> crate::Result<Self> {
+        Self::with(address, len, |_| prot)
+    }
+
+    /// Adds write permission to the pages covering `address..address + len`,
+    /// keeping the rest of the protection of each mapping.
+    /// # Errors
+    /// Same as [`ProtectionGuard::new`].
+    pub fn writable(address: usize, len: usize) -> crate::Result<Self> {
+        Self::with(address, len, |prot| prot | Protection::W)
+    }
+
+    /// Changes the protection of every affected mapping to `f` of its original protection.
+    fn with(
+        address: usize,
+        len: usize,
+        f: impl Fn(Protection) -> Protection,
+    ) -> crate::Result<Self> {
+        let start = address / PAGE_SIZE * PAGE_SIZE;
+        let end = address
+            .checked_add(len)
+            .and_then(|end| end.checked_next_multiple_of(PAGE_SIZE))
+            .ok_or(MfError::InvalidAddress(address))?;
+
+        let mut maps = CurrentProcess.maps()?;
+        maps.sort_unstable_by_key(|r| r.from);
+
+        let mut original = Vec::new();
+        let mut cursor = start;
+        for r in maps.iter().filter(|r| r.to > start && r.from < end) {
+            if r.from > cursor {
+                break;
+            }
+
+            original.push(MemoryRegion {
+                from: cursor,
+                to: r.to.min(end),
+                prot: r.prot,
+            });
+            cursor = r.to.min(end);
+        }
+
+        if cursor < end {
+            return Err(MfError::InvalidAddress(cursor.max(address)));
+        }
+
+        let guard = Self { original };
+        for r in &guard.original {
+            protect(r.from, r.to - r.from, f(r.prot))?;
+        }
+        Ok(guard)
+    }
+
+    /// Protection of the affected pages before the change, sorted by address.
+    #[inline]
+    pub fn original(&self) -> &[MemoryRegion] {
+        &self.original
+    }
+
+    /// Restores the original protection now, returning the first error.
+    pub fn restore(mut self) -> crate::Result<()> {
+        self.restore_all()
+    }
+
+    fn restore_all(&mut self) -> crate::Result<()> {
+        let mut result = Ok(());
+        for r in core::mem::take(&mut self.original) {
+            let restored = protect(r.from, r.to - r.from, r.prot);
+            result = result.and(restored);
+        }
+        result
+    }
+}
+
+impl Drop for ProtectionGuard {
+    fn drop(&mut self) {
+        _ = self.restore_all();
+    }
+}
@@ -0,0 +1,500 @@
+use super::{allocate, free, protect, CurrentProcess};
+use crate::{
+    types::Protection,
+    x86::{decode, relocate, Branch},
+    AppliedPatch, MemorySource, MfError, Patch, PAGE_SIZE,
+};
+use core::mem::ManuallyDrop;
+
+/// Size of `jmp rel32`, the least amount of bytes overwritten at the target.
+const JUMP_LEN: usize = 5;
+/// Allocations are aligned to 64KB on Windows.
+const GRANULARITY: usize = 0x10000;
+/// Farthest the trampoline page can be from the target, so rel32 operands can reach both.
+const MAX_DISTANCE: usize = 0x7FF0_0000;
+
+/// Redirects calls of a function to a detour, the original function stays callable
+/// through a trampoline.
+/// # Behavior
+/// Instructions overwritten by the jump to the detour are relocated into the trampoline,
+/// which is allocated within 2GB of the target. RIP-relative operands and relative branches
+/// are adjusted to work from their new location.
+///
+/// The hook is created disabled. It's disabled and the trampoline is released when dropped,
+/// unless the original bytes can't be written back.
+/// ```
+/// # use memflex::internal::InlineHook;
+/// #[inline(never)]
+/// extern "C" fn add(a: i32, b: i32) -> i32 {
+///     a + b
+/// }
+///
+/// extern "C" fn sub(a: i32, b: i32) -> i32 {
+///     a - b
+/// }
+///
+/// let mut hook = unsafe { InlineHook::new(add as *const u8, sub as *const u8) }.unwrap();
+/// let original: extern "C" fn(i32, i32) -> i32 = unsafe { std::mem::transmute(hook.trampoline()) };
+///
+/// unsafe { hook.enable() }.unwrap();
+/// assert_eq!(std::hint::black_box(add)(5, 3), 2);
+/// assert_eq!(original(5, 3), 8);
+///
+/// unsafe { hook.unhook() }.unwrap();
+/// assert_eq!(std::hint::black_box(add)(5, 3), 8);
+/// ```
+pub struct InlineHook {
+    detour: usize,
+    redirect: Redirect,
+}
+
+impl InlineHook {
+    /// Creates a disabled hook that redirects `target` to `detour`.
+    /// # Errors
+    /// * [`MfError::InvalidInstruction`] if an overwritten instruction can't be relocated,
+    ///   or the function is shorter than the jump and isn't followed by padding.
+    /// * [`MfError::NoMemoryNearby`] if the trampoline can't be allocated within 2GB of the target.
+    /// * [`MfError::InvalidAddress`] if the code of the target can't be read.
+    /// # Safety
+    /// * `target` must point to the start of a function, nothing else may branch into
+    ///   its first 5 bytes.
+    /// * `detour` must be a function with the same signature and calling convention.
+    pub unsafe fn new(target: *const u8, detour: *const u8) -> crate::Result<Self> {
+        let detour = detour as usize;
+        // Detour can be anywhere, so the relay always jumps to an absolute address.
+        let relay = [
+            &[0xFF, 0x25, 0, 0, 0, 0][..],
+            &(detour as u64).to_le_bytes(),
+        ]
+        .concat();
+
+        Ok(Self {
+            detour,
+            redirect: Redirect::new(target as usize, relay)?,
+        })
+    }
+
+    /// Address of the hooked function.
+    #[inline]
+    pub fn target(&self) -> *const u8 {
+        self.redirect.target as _
+    }
+
+    /// Address of the detour.
+    #[inline]
+    pub fn detour(&self) -> *const u8 {
+        self.detour as _
+    }
+
+    /// Calls the original function when called with the signature of the target.
+    #[inline]
+    pub fn trampoline(&self) -> *const u8 {
+        self.redirect.relocated() as _
+    }
+
+    /// Checks if calls of the target are redirected.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.redirect.applied.is_some()
+    }
+
+    /// Writes the jump to the detour.
+    /// # Errors
+    /// * [`MfError::UnexpectedBytes`] if the code of the target changed since the hook was created.
+    /// # Safety
+    /// * No thread may be executing the overwritten instructions.
+    pub unsafe fn enable(&mut self) -> crate::Result<()> {
+        self.redirect.enable()
+    }
+
+    /// Writes the original instructions back.
+    /// # Safety
+    /// * No thread may be executing the overwritten instructions.
+    pub unsafe fn disable(&mut self) -> crate::Result<()> {
+        self.redirect.disable()
+    }
+
+    /// Disables the hook and releases the trampoline, returning the error if it fails.
+    /// # Safety
+    /// * No thread may be executing the overwritten instructions or the trampoline.
+    pub unsafe fn unhook(mut self) -> crate::Result<()> {
+        self.disable()
+    }
+}
+
+/// Jump from the target to a page allocated near it. The page starts with the given code,
+/// followed by the overwritten instructions and a jump back to the target.
+struct Redirect {
+    target: usize,
+    block: usize,
+    /// Length of the code before the relocated instructions.
+    head: usize,
+    jump: Patch,
+    applied: Option<AppliedPatch<'static, CurrentProcess>>,
+}
+
+impl Redirect {
+    unsafe fn new(target: usize, head: Vec<u8>) -> crate::Result<Self> {
+        let mut code = [0; 32];
+        let read = CurrentProcess.read_buf(target, &mut code).unwrap_or(0);
+        if read == 0 {
+            return Err(MfError::InvalidAddress(target));
+        }
+        let len = stolen_len(&code[..read], target)?;
+
+        let block = allocate_near(target, PAGE_SIZE)?;
+        if let Err(e) = write_block(block, &head, &code[..len], target) {
+            _ = free(block, PAGE_SIZE);
+            return Err(e);
+        }
+
+        let mut jump = Patch::jump(target, block);
+        jump.bytes.resize(len, 0x90);
+        Ok(Self {
+            target,
+            block,
+            head: head.len(),
+            jump: jump.expect(&code[..len]),
+            applied: None,
+        })
+    }
+
+    /// Address of the relocated instructions.
+    fn relocated(&self) -> usize {
+        self.block + self.head
+    }
+
+    unsafe fn enable(&mut self) -> crate::Result<()> {
+        if self.applied.is_none() {
+            self.applied = Some(self.jump.clone().apply(&CurrentProcess)?);
+        }
+        Ok(())
+    }
+
+    unsafe fn disable(&mut self) -> crate::Result<()> {
+        if let Some(applied) = &mut self.applied {
+            applied.disable()?;
+            self.applied = None;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Redirect {
+    fn drop(&mut self) {
+        // Page is leaked if the target still jumps into it.
+        if unsafe { self.disable() }.is_ok() {
+            _ = free(self.block, PAGE_SIZE);
+        }
+    }
+}
+
+/// Length of the whole instructions covering the first [`JUMP_LEN`] bytes of `code`.
+/// Bytes after a `ret` or `jmp` are only overwritten if they are padding.
+fn stolen_len(code: &[u8], address: usize) -> crate::Result<usize> {
+    let mut len = 0;
+    let mut ended = false;
+
+    while len < JUMP_LEN {
+        let insn = decode(&code[len..]).ok_or(MfError::InvalidInstruction(address + len))?;
+        let bytes = &code[len..len + insn.len];
+        let prefixes = bytes
+            .iter()
+            .take_while(|b| matches!(b, 0x66 | 0x2E | 0xF2 | 0xF3));
+        let op = &bytes[prefixes.count()..];
+
+        if ended && !matches!(op, [0xCC] | [0x90] | [0x0F, 0x1F, ..]) {
+            return Err(MfError::InvalidInstruction(address + len));
+        }
+        ended |= insn.branch == Some(Branch::Jmp) || matches!(op, [0xC3] | [0xC2, ..]);
+        len += insn.len;
+    }
+
+    Ok(len)
+}
+
+/// Writes `head` followed by the relocated `code` and a jump back to the target,
+/// then makes the page executable.
+fn write_block(block: usize, head: &[u8], code: &[u8], target: usize) -> crate::Result<()> {
+    let relocated = block + head.len();
+    let mut bytes = relocate(code, target, relocated)?;
+    bytes.extend(Patch::jump(relocated + bytes.len(), target + code.len()).bytes);
+
+    let bytes = [head, &bytes].concat();
+    unsafe { (block as *mut u8).copy_from_nonoverlapping(bytes.as_ptr(), bytes.len()) };
+    protect(block, PAGE_SIZE, Protection::RX)
+}
+
+/// Allocates read/write memory within [`MAX_DISTANCE`] of `address`,
+/// trying the free gaps closest to it first.
+fn allocate_near(address: usize, len: usize) -> crate::Result<usize> {
+    let mut maps = CurrentProcess.maps()?;
+    maps.sort_unstable_by_key(|r| r.from);
+
+    let low = address.saturating_sub(MAX_DISTANCE).max(GRANULARITY);
+    let high = address.saturating_add(MAX_DISTANCE);
+
+    let mut candidates = Vec::new();
+    let mut gap = 0;
+    for (from, to) in maps.iter().map(|r| (r.from, r.to)).chain([(high, high)]) {
+        let start = gap.max(low).next_multiple_of(GRANULARITY);
+        let end = from.min(high);
+        gap = gap.max(to);
+
+        if let Some(last) = end.checked_sub(len).map(|e| e / GRANULARITY * GRANULARITY) {
+            if start <= last {
+                candidates.push(if last < address { last } else { start });
+            }
+        }
+    }
+    candidates.sort_unstable_by_key(|c| c.abs_diff(address));
+
+    for candidate in candidates {
+        let Ok(block) = allocate(Some(candidate), len, Protection::RW) else {
+            continue;
+        };
+        let block = block as usize;
+        if block.abs_diff(address) <= MAX_DISTANCE {
+            return Ok(block);
+        }
+        _ = free(block, len);
+    }
+
+    Err(MfError::NoMemoryNearby(address))
+}
+
+/// Registers saved by a [`MidHook`], general purpose registers are in the order of their encoding.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(missing_docs)]
+pub struct Context {
+    pub xmm: [u128; 16],
+    pub rax: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rbx: u64,
+    /// Stack pointer the code continues with.
+    pub rsp: u64,
+    pub rbp: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub r8: u64,
+    pub r9: u64,
+    pub r10: u64,
+    pub r11: u64,
+    pub r12: u64,
+    pub r13: u64,
+    pub r14: u64,
+    pub r15: u64,
+    pub rflags: u64,
+}
+
+// The context is allocated 16 byte aligned on the stack and saved with `movdqa`,
+// which needs the stack to stay aligned after it.
+const _: () = assert!(core::mem::size_of::<Context>().is_multiple_of(16));
+
+/// Offset of the general purpose register with the encoding `reg` in [`Context`].
+const fn gpr(reg: u8) -> i32 {
+    256 + reg as i32 * 8
+}
+
+const RAX: u8 = 0;
+const RCX: u8 = 1;
+const RBX: u8 = 3;
+const RSP: u8 = 4;
+/// Offset of `rflags` in [`Context`].
+const RFLAGS: i32 = gpr(16);
+/// Red zone below the stack pointer that the hooked code may use, followed by `rflags` and `rax`.
+const RED_ZONE: i32 = 128;
+
+type Callback = dyn Fn(&mut Context) + Send + Sync;
+
+/// Calls a callback with the registers at an instruction, then executes the instruction
+/// with the registers the callback left in the [`Context`].
+/// # Behavior
+/// The instructions overwritten by the jump to the callback are relocated, the same way as
+/// with [`InlineHook`]. The callback runs on the stack of the hooked code, below its red zone.
+/// Panics in the callback abort the process.
+///
+/// The hook is created disabled. It's disabled and its memory is released when dropped,
+/// unless the original bytes can't be written back.
+/// ```
+/// # use memflex::internal::MidHook;
+/// #[inline(never)]
+/// extern "C" fn double(x: u64) -> u64 {
+///     x * 2
+/// }
+///
+/// let mut hook = unsafe {
+///     MidHook::new(double as *const u8, |ctx| {
+///         assert_eq!(ctx.rdi, 5);
+///         ctx.rdi = 10;
+///     })
+/// }
+/// .unwrap();
+///
+/// unsafe { hook.enable() }.unwrap();
+/// assert_eq!(std::hint::black_box(double)(5), 20);
+/// ```
+pub struct MidHook {
+    redirect: Redirect,
+    /// Dropped only if the redirect isn't in use anymore.
+    callback: ManuallyDrop<Box<Callback>>,
+}
+
+impl MidHook {
+    /// Creates a disabled hook that calls `callback` before the instruction at `address`.
+    /// # Errors
+    /// * Errors of [`InlineHook::new`].
+    /// # Safety
+    /// * `address` must point to the start of an instruction, nothing else may branch into
+    ///   the 5 bytes after it.
+    /// * Registers changed by the callback must be valid for the hooked code.
+    pub unsafe fn new<F>(address: *const u8, callback: F) -> crate::Result<Self>
+    where
+        F: Fn(&mut Context) + Send + Sync + 'static,
+    {
+        let callback = Box::new(callback);
+        let stub = context_stub(
+            thunk::<F> as *const () as usize,
+            &*callback as *const F as usize,
+        );
+
+        Ok(Self {
+            redirect: Redirect::new(address as usize, stub)?,
+            callback: ManuallyDrop::new(callback),
+        })
+    }
+
+    /// Address of the hooked instruction.
+    #[inline]
+    pub fn address(&self) -> *const u8 {
+        self.redirect.target as _
+    }
+
+    /// Checks if the callback is called.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.redirect.applied.is_some()
+    }
+
+    /// Writes the jump to the callback.
+    /// # Errors
+    /// * [`MfError::UnexpectedBytes`] if the code changed since the hook was created.
+    /// # Safety
+    /// * No thread may be executing the overwritten instructions.
+    pub unsafe fn enable(&mut self) -> crate::Result<()> {
+        self.redirect.enable()
+    }
+
+    /// Writes the original instructions back.
+    /// # Safety
+    /// * No thread may be executing the overwritten instructions.
+    pub unsafe fn disable(&mut self) -> crate::Result<()> {
+        self.redirect.disable()
+    }
+
+    /// Disables the hook and releases its memory, returning the error if it fails.
+    /// # Safety
+    /// * No thread may be executing the overwritten instructions or the callback.
+    pub unsafe fn unhook(mut self) -> crate::Result<()> {
+        self.disable()
+    }
+}
+
+impl Drop for MidHook {
+    fn drop(&mut self) {
+        if unsafe { self.redirect.disable() }.is_ok() {
+            unsafe { ManuallyDrop::drop(&mut self.callback) };
+        }
+    }
+}
+
+extern "C" fn thunk<F: Fn(&mut Context)>(ctx: &mut Context, callback: &F) {
+    callback(ctx)
+}
+
+/// Code that saves the registers into a [`Context`] on the stack, calls `thunk(context, data)`
+/// and restores the registers from the context.
+fn context_stub(thunk: usize, data: usize) -> Vec<u8> {
+    let size = core::mem::size_of::<Context>() as i32;
+    let mut code = vec![];
+
+    // lea rsp, [rsp - 128]; pushfq; push rax; mov rax, rsp
+    code.extend([0x48, 0x8D, 0x64, 0x24, 0x80, 0x9C, 0x50, 0x48, 0x89, 0xE0]);
+    // and rsp, -16; sub rsp, size
+    code.extend([0x48, 0x83, 0xE4, 0xF0, 0x48, 0x81, 0xEC]);
+    code.extend(size.to_le_bytes());
+
+    for reg in (0..16).filter(|&r| r != RAX && r != RSP) {
+        code.extend(mov(0x89, reg, RSP, gpr(reg)));
+    }
+    // Values pushed before the stack was aligned.
+    code.extend(mov(0x8B, RCX, RAX, 0));
+    code.extend(mov(0x89, RCX, RSP, gpr(RAX)));
+    code.extend(mov(0x8B, RCX, RAX, 8));
+    code.extend(mov(0x89, RCX, RSP, RFLAGS));
+    code.extend(mov(0x8D, RCX, RAX, RED_ZONE + 16));
+    code.extend(mov(0x89, RCX, RSP, gpr(RSP)));
+    for reg in 0..16 {
+        code.extend(movdqa(0x7F, reg, RSP, reg as i32 * 16));
+    }
+
+    // Arguments are passed in both conventions, `rdi` and `rsi` on SysV and `rcx` and `rdx`
+    // on Win64, so the stub doesn't depend on the calling convention of `extern "C"`.
+    // mov rbx, rsp; mov rdi, rsp; mov rcx, rsp
+    code.extend([0x48, 0x89, 0xE3, 0x48, 0x89, 0xE7, 0x48, 0x89, 0xE1]);
+    // mov rsi, data; mov rdx, data
+    code.extend([0x48, 0xBE]);
+    code.extend((data as u64).to_le_bytes());
+    code.extend([0x48, 0xBA]);
+    code.extend((data as u64).to_le_bytes());
+    // sub rsp, 32; mov rax, thunk; call rax
+    code.extend([0x48, 0x83, 0xEC, 0x20, 0x48, 0xB8]);
+    code.extend((thunk as u64).to_le_bytes());
+    code.extend([0xFF, 0xD0]);
+
+    for reg in 0..16 {
+        code.extend(movdqa(0x6F, reg, RBX, reg as i32 * 16));
+    }
+    // `rax` and `rflags` are restored from below the new stack pointer.
+    code.extend(mov(0x8B, RAX, RBX, gpr(RSP)));
+    code.extend(mov(0x8D, RAX, RAX, -(RED_ZONE + 16)));
+    code.extend(mov(0x8B, RCX, RBX, gpr(RAX)));
+    code.extend(mov(0x89, RCX, RAX, 0));
+    code.extend(mov(0x8B, RCX, RBX, RFLAGS));
+    code.extend(mov(0x89, RCX, RAX, 8));
+    for reg in (0..16).filter(|&r| r != RAX && r != RBX && r != RSP) {
+        code.extend(mov(0x8B, reg, RBX, gpr(reg)));
+    }
+    // mov rsp, rax
+    code.extend([0x48, 0x89, 0xC4]);
+    code.extend(mov(0x8B, RBX, RBX, gpr(RBX)));
+    // pop rax; popfq; lea rsp, [rsp + 128]
+    code.extend([0x58, 0x9D, 0x48, 0x8D, 0xA4, 0x24]);
+    code.extend(RED_ZONE.to_le_bytes());
+
+    code
+}
+
+/// `op reg, [base + disp]` with a 64 bit operand and a 32 bit displacement.
+fn mov(op: u8, reg: u8, base: u8, disp: i32) -> Vec<u8> {
+    let rex = 0x48 | (reg >> 3) << 2 | base >> 3;
+    modrm_disp32(&[rex, op], reg, base, disp)
+}
+
+/// `movdqa xmm, [base + disp]` (`0x6F`) or `movdqa [base + disp], xmm` (`0x7F`).
+fn movdqa(op: u8, xmm: u8, base: u8, disp: i32) -> Vec<u8> {
+    let rex = 0x40 | (xmm >> 3) << 2 | base >> 3;
+    modrm_disp32(&[0x66, rex, 0x0F, op], xmm, base, disp)
+}
+
+fn modrm_disp32(head: &[u8], reg: u8, base: u8, disp: i32) -> Vec<u8> {
+    let mut code = head.to_vec();
+    code.push(0x80 | (reg & 7) << 3 | base & 7);
+    if base & 7 == RSP {
+        code.push(0x24);
+    }
+    code.extend(disp.to_le_bytes());
+    code
+}
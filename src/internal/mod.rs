@@ -8,16 +8,46 @@ mod unix;
 #[cfg(unix)]
 pub use unix::*;
 
+mod checked;
+pub use checked::*;
+mod guard;
+pub use guard::*;
+mod vmt;
+pub use vmt::*;
+#[cfg(all(unix, any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod got;
+#[cfg(all(unix, any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub use got::*;
+#[cfg(target_arch = "x86_64")]
+mod hook;
+#[cfg(target_arch = "x86_64")]
+pub use hook::*;
+
+/// Current process as a [`crate::MemorySource`].
+/// # Behavior
+/// Memory is read through the same system calls that are used for other processes,
+/// so reading unmapped or protected memory returns an error instead of faulting.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CurrentProcess;
+
 /// Returns an information about current module
 /// # Behavior
-/// Looks up module by looking up RIP register.
-/// Can return `None` if the module was manually mapped and not linked in ldr.
-#[cfg(all(windows, feature = "alloc"))]
+/// Looks up module by looking up RIP register on windows,
+/// and by the address of this function on linux.
+/// Can return `None` if the module was manually mapped and not linked in ldr
+/// (or not registered with the dynamic linker on linux).
+#[cfg(feature = "alloc")]
 pub fn current_module() -> Option<crate::types::ModuleInfoWithName> {
-    let mut rip: usize;
-    unsafe {
-        core::arch::asm!("lea {}, [rip]", out(reg) rip);
-    }
+    #[cfg(windows)]
+    let rip: usize = {
+        let mut rip;
+        unsafe {
+            core::arch::asm!("lea {}, [rip]", out(reg) rip);
+        }
+        rip
+    };
+    #[cfg(unix)]
+    let rip = current_module as *const () as usize;
 
     modules().find(|m| rip < m.base as usize + m.size && rip > m.base as usize)
 }
@@ -31,3 +61,12 @@ pub fn find_pattern_in_module(
     let module = find_module_by_name(module_name)?;
     unsafe { Some(crate::find_pattern(pat, module.base, module.size)) }
 }
+
+/// Searches for a pattern in the specified module, returning matches with their capture slots.
+pub fn find_matches_in_module(
+    pat: impl crate::Matcher,
+    module_name: &str,
+) -> Option<impl Iterator<Item = crate::Match>> {
+    let m = crate::Match::new(&pat, 0);
+    Some(find_pattern_in_module(pat, module_name)?.map(move |a| m.at(a as usize)))
+}
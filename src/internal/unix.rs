@@ -1,4 +1,261 @@
-use crate::{types::Protection, MfError};
+use crate::{
+    is_module_name,
+    types::MemoryRegion,
+    types::{
+        elf::{ElfDyn, DT_NULL, DT_SONAME, DT_STRTAB},
+        ModuleInfo, ModuleInfoWithName, Protection,
+    },
+    Image, Match, Matcher, MemorySink, MemorySource, MfError, PatternSet, Scanner, Signature,
+    SignatureChecker, SignatureReport,
+};
+use core::{
+    ffi::{c_int, c_void, CStr},
+    slice::from_raw_parts,
+};
+use libc::{dl_phdr_info, Elf64_Phdr, PF_R, PT_DYNAMIC, PT_LOAD};
+use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::PathBuf};
+
+/// Object loaded by the dynamic linker.
+pub(super) struct LoadedObject<'a>(&'a dl_phdr_info);
+
+impl LoadedObject<'_> {
+    fn phdrs(&self) -> &[Elf64_Phdr] {
+        unsafe { from_raw_parts(self.0.dlpi_phdr, self.0.dlpi_phnum as usize) }
+    }
+
+    /// Full path to the object, main executable has an empty name in the link map.
+    fn path(&self) -> Option<PathBuf> {
+        let name = unsafe { CStr::from_ptr(self.0.dlpi_name) };
+        if name.is_empty() {
+            std::env::current_exe().ok()
+        } else {
+            Some(OsStr::from_bytes(name.to_bytes()).into())
+        }
+    }
+
+    /// Entries of the dynamic section, without the terminating `DT_NULL`.
+    pub(super) fn dynamic(&self) -> impl Iterator<Item = ElfDyn> + '_ {
+        let dynamic = self.phdrs().iter().find(|p| p.p_type == PT_DYNAMIC);
+        let mut entry = dynamic.map(|d| (self.bias() + d.p_vaddr as usize) as *const ElfDyn);
+
+        core::iter::from_fn(move || unsafe {
+            let current = *entry?;
+            if current.tag == DT_NULL {
+                return None;
+            }
+            entry = entry.map(|e| e.add(1));
+            Some(current)
+        })
+    }
+
+    /// Address the object is loaded at, relative to the addresses in its headers.
+    pub(super) fn bias(&self) -> usize {
+        self.0.dlpi_addr as usize
+    }
+
+    /// Address from a dynamic entry.
+    /// glibc relocates dynamic entries in place, other loaders (and vdso) don't.
+    pub(super) fn dynamic_address(&self, val: u64) -> usize {
+        let val = val as usize;
+        if val < self.bias() {
+            val + self.bias()
+        } else {
+            val
+        }
+    }
+
+    /// Reads `DT_SONAME` from the dynamic section.
+    fn soname(&self) -> Option<&CStr> {
+        let (mut strtab, mut soname) = (None, None);
+        for entry in self.dynamic() {
+            match entry.tag {
+                DT_STRTAB => strtab = Some(self.dynamic_address(entry.val)),
+                DT_SONAME => soname = Some(entry.val as usize),
+                _ => {}
+            }
+        }
+
+        unsafe { Some(CStr::from_ptr((strtab? + soname?) as _)) }
+    }
+
+    fn loads(&self) -> impl Iterator<Item = &Elf64_Phdr> {
+        self.phdrs().iter().filter(|p| p.p_type == PT_LOAD)
+    }
+
+    pub(super) fn info(&self) -> ModuleInfo {
+        let from = self.loads().map(|p| p.p_vaddr).min().unwrap_or_default();
+        let to = self
+            .loads()
+            .map(|p| p.p_vaddr + p.p_memsz)
+            .max()
+            .unwrap_or_default();
+
+        ModuleInfo {
+            base: (self.0.dlpi_addr + from) as _,
+            size: (to - from) as usize,
+        }
+    }
+
+    /// Returns `(start, len)` of every readable segment.
+    fn readable_segments(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.loads()
+            .filter(|p| p.p_flags & PF_R != 0)
+            .map(|p| ((self.0.dlpi_addr + p.p_vaddr) as usize, p.p_memsz as usize))
+    }
+
+    fn is_named(&self, name: &str) -> bool {
+        let file_name = self
+            .path()
+            .and_then(|p| Some(is_module_name(p.file_name()?.as_bytes(), name)));
+
+        file_name.unwrap_or(false)
+            || self
+                .soname()
+                .is_some_and(|s| is_module_name(s.to_bytes(), name))
+    }
+}
+
+/// Calls `f` on every loaded object until it returns `Some`.
+pub(super) fn find_loaded<T, F: FnMut(&LoadedObject) -> Option<T>>(f: F) -> Option<T> {
+    unsafe extern "C" fn callback<T, F: FnMut(&LoadedObject) -> Option<T>>(
+        info: *mut dl_phdr_info,
+        _: usize,
+        data: *mut c_void,
+    ) -> c_int {
+        let (f, out) = &mut *(data as *mut (F, Option<T>));
+        *out = f(&LoadedObject(&*info));
+        out.is_some() as _
+    }
+
+    let mut state = (f, None);
+    unsafe {
+        libc::dl_iterate_phdr(Some(callback::<T, F>), &mut state as *mut _ as _);
+    }
+    state.1
+}
+
+/// Searches for a module by its name.
+/// # Behavior
+/// Function iterates over objects loaded by the dynamic linker and compares `module_name`
+/// against their file names and sonames, names are case sensitive.
+/// The main executable is matched by the file name of the executable.
+pub fn find_module_by_name(module_name: &str) -> Option<ModuleInfo> {
+    find_loaded(|o| o.is_named(module_name).then(|| o.info()))
+}
+
+/// Returns an iterator over all modules in the current process.
+/// # Behavior
+/// Modules are listed in the dynamic linker's load order, starting with the main executable.
+pub fn modules() -> impl Iterator<Item = ModuleInfoWithName> {
+    let mut modules = vec![];
+    find_loaded::<(), _>(|o| {
+        let ModuleInfo { base, size } = o.info();
+        let name = o
+            .path()
+            .and_then(|p| Some(p.file_name()?.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+
+        modules.push(ModuleInfoWithName { base, size, name });
+        None
+    });
+
+    modules.into_iter()
+}
+
+/// Returns `(start, len)` of every readable segment of the specified module.
+fn module_segments(module_name: &str) -> Option<Vec<(usize, usize)>> {
+    find_loaded(|o| {
+        o.is_named(module_name)
+            .then(|| o.readable_segments().collect::<Vec<_>>())
+    })
+}
+
+/// Searches for a pattern in the readable segments of the specified module.
+pub fn find_pattern_in_module(
+    pat: impl Matcher,
+    module_name: &str,
+) -> Option<impl Iterator<Item = *const u8>> {
+    let segments = module_segments(module_name)?;
+
+    let scanner = Scanner::new(pat);
+    let mut segments = segments.into_iter();
+    let mut current = segments.next();
+    let mut from = 0;
+
+    Some(core::iter::from_fn(move || loop {
+        let (start, len) = current?;
+        let data = unsafe { from_raw_parts(start as *const u8, len) };
+
+        if let Some(found) = scanner.find(data, from) {
+            from = found + 1;
+            return Some((start + found) as *const u8);
+        }
+
+        current = segments.next();
+        from = 0;
+    }))
+}
+
+/// Searches for every pattern of the set in the readable segments of the specified module,
+/// returning ids of the patterns with their matches.
+pub fn find_pattern_set_in_module<'a>(
+    set: &'a PatternSet,
+    module_name: &str,
+) -> Option<impl Iterator<Item = (usize, Match)> + 'a> {
+    let segments = module_segments(module_name)?;
+
+    Some(
+        segments
+            .into_iter()
+            .flat_map(move |(start, len)| unsafe { crate::find_pattern_set(set, start as _, len) }),
+    )
+}
+
+/// Checks every pattern of the checker against a loaded module.
+/// Returns [`MfError::ModuleNotFound`] if `module` doesn't describe a loaded module.
+/// # Behavior
+/// Only the readable `PT_LOAD` segments are scanned,
+/// gaps between them and segments without read access are skipped.
+pub fn check_signatures(
+    checker: &SignatureChecker,
+    module: &ModuleInfo,
+) -> crate::Result<SignatureReport> {
+    let segments = find_loaded(|o| {
+        let info = o.info();
+        (info.base == module.base && info.size == module.size)
+            .then(|| o.readable_segments().collect::<Vec<_>>())
+    })
+    .ok_or(MfError::ModuleNotFound)?;
+
+    let found = segments.into_iter().flat_map(|(start, len)| unsafe {
+        crate::find_pattern_set(checker.patterns(), start as _, len)
+    });
+    Ok(checker.report_matches(module.base as usize, found))
+}
+
+/// Creates a signature for the code at `address`, growing it until it is unique
+/// within the readable segments of the module that contains the address.
+/// Returns `None` if the address doesn't belong to any module.
+/// # Behavior
+/// Relative branch targets, RIP-relative displacements and operands
+/// that point into the module are wildcarded.
+/// The pattern won't be longer than `max_len` bytes.
+pub fn make_signature(address: *const u8, max_len: usize) -> Option<Signature> {
+    let address = address as usize;
+    let (range, segments) = find_loaded(|o| {
+        let ModuleInfo { base, size } = o.info();
+        let range = base as usize..base as usize + size;
+        range
+            .contains(&address)
+            .then(|| (range, o.readable_segments().collect::<Vec<_>>()))
+    })?;
+
+    let segments = segments
+        .into_iter()
+        .map(|(start, len)| (start, unsafe { from_raw_parts(start as *const u8, len) }))
+        .collect();
+    crate::make_signature(&Image { segments, range }, address, max_len)
+}
 
 /// Changes the protection of a memory region
 pub fn protect(address: usize, len: usize, prot: Protection) -> crate::Result<()> {
@@ -23,7 +280,7 @@ pub fn allocate(address: Option<usize>, len: usize, prot: Protection) -> crate::
             0,
         );
 
-        if !addr.is_null() {
+        if addr != libc::MAP_FAILED {
             Ok(addr as _)
         } else {
             MfError::last()
@@ -43,6 +300,65 @@ pub fn free(address: usize, len: usize) -> crate::Result<()> {
 }
 
 /// Returns the id of the current process
+/// # Behavior
+/// Makes the system call directly, so it keeps working while `getpid` is hooked.
 pub fn pid() -> u32 {
-    unsafe { libc::getpid() as _ }
+    // Don't replace it with `libc::getpid`, the call goes through the GOT
+    // that `ImportHook` patches, and `CurrentProcess` reads and maps the process by this id.
+    // A hooked `getpid` would make them read another process or fail while it's hooked.
+    unsafe { libc::syscall(libc::SYS_getpid) as _ }
+}
+
+impl MemorySource for super::CurrentProcess {
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        unsafe {
+            let read = libc::process_vm_readv(
+                pid() as _,
+                &libc::iovec {
+                    iov_base: buf.as_mut_ptr() as _,
+                    iov_len: buf.len(),
+                },
+                1,
+                &libc::iovec {
+                    iov_base: address as _,
+                    iov_len: buf.len(),
+                },
+                1,
+                0,
+            );
+
+            if read == -1 {
+                MfError::last()
+            } else {
+                Ok(read as usize)
+            }
+        }
+    }
+
+    fn modules(&self) -> crate::Result<Vec<ModuleInfoWithName>> {
+        Ok(modules().collect())
+    }
+
+    /// Matches sonames as well, like [`find_module_by_name`].
+    fn find_module(&self, name: &str) -> crate::Result<ModuleInfoWithName> {
+        let base = find_module_by_name(name)
+            .ok_or(MfError::ModuleNotFound)?
+            .base;
+        modules()
+            .find(|m| m.base == base)
+            .ok_or(MfError::ModuleNotFound)
+    }
+
+    fn maps(&self) -> crate::Result<Vec<MemoryRegion>> {
+        crate::proc_maps(pid())
+    }
+}
+
+impl MemorySink for super::CurrentProcess {
+    /// Adds write permission to the memory for the duration of the write.
+    fn write_buf(&self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        let _guard = super::ProtectionGuard::writable(address, buf.len())?;
+        unsafe { core::ptr::copy(buf.as_ptr(), address as *mut u8, buf.len()) };
+        Ok(buf.len())
+    }
 }
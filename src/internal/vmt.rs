@@ -0,0 +1,199 @@
+use super::CurrentProcess;
+use crate::{
+    types::{VmtEntry, VmtPtr},
+    AppliedPatch, Patch,
+};
+use core::mem::transmute_copy;
+
+impl VmtPtr {
+    /// Replaces the function of `entry` in the table with `detour`,
+    /// the original function is written back when the hook is dropped.
+    /// # Behavior
+    /// The table is shared by every object of the type, use [`ShadowVmt`] to hook a single object.
+    /// # Errors
+    /// * Errors of [`Patch::apply`].
+    /// # Safety
+    /// * `self` must be a valid pointer to a virtual method table that contains `entry`.
+    /// * `F` must be a function pointer type.
+    pub unsafe fn hook<F: Copy>(&self, entry: VmtEntry<F>, detour: F) -> crate::Result<VmtHook<F>> {
+        let slot = self.vmt.add(entry.index()) as usize;
+        let original = self.at::<usize>(entry.index());
+
+        let patch = Patch::new(slot, transmute_copy::<F, usize>(&detour).to_ne_bytes())
+            .expect(original.to_ne_bytes())
+            .apply(&CurrentProcess)?;
+
+        Ok(VmtHook {
+            entry,
+            original,
+            patch,
+        })
+    }
+}
+
+/// Function swapped in a virtual method table, see [`VmtPtr::hook`].
+pub struct VmtHook<F> {
+    entry: VmtEntry<F>,
+    original: usize,
+    patch: AppliedPatch<'static, CurrentProcess>,
+}
+
+impl<F: Copy> VmtHook<F> {
+    /// The hooked entry.
+    #[inline]
+    pub fn entry(&self) -> VmtEntry<F> {
+        self.entry
+    }
+
+    /// Function the table contained before the hook.
+    #[inline]
+    pub fn original(&self) -> F {
+        unsafe { transmute_copy(&self.original) }
+    }
+
+    /// Checks if the table contains the detour.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.patch.is_applied()
+    }
+
+    /// Writes the detour into the table again.
+    pub fn enable(&mut self) -> crate::Result<()> {
+        self.patch.enable()
+    }
+
+    /// Writes the original function back, the hook can be enabled again.
+    pub fn disable(&mut self) -> crate::Result<()> {
+        self.patch.disable()
+    }
+
+    /// Writes the original function back now, returning the error if it fails.
+    pub fn restore(self) -> crate::Result<()> {
+        self.patch.restore()
+    }
+}
+
+/// Copy of a virtual method table installed on a single object,
+/// functions of the copy can be replaced without affecting other objects.
+/// # Behavior
+/// `prefix` words before the entries are copied too, compilers keep RTTI there,
+/// see [`ShadowVmt::RTTI_PREFIX`].
+/// The object gets its original table back when the shadow is dropped.
+/// ```
+/// # use memflex::{internal::ShadowVmt, types::VmtEntry};
+/// #[repr(C)]
+/// struct Object {
+///     vmt: &'static [extern "C" fn(&Object) -> i32; 1],
+/// }
+///
+/// extern "C" fn one(_: &Object) -> i32 { 1 }
+/// extern "C" fn two(_: &Object) -> i32 { 2 }
+///
+/// let (mut hooked, other) = (Object { vmt: &[one] }, Object { vmt: &[one] });
+/// let entry = VmtEntry::<extern "C" fn(&Object) -> i32>::new(0);
+///
+/// let mut shadow = unsafe { ShadowVmt::new(&mut hooked, 0, 1) };
+/// assert_eq!(unsafe { shadow.hook(entry, two) } as usize, one as *const () as usize);
+/// assert_eq!((hooked.vmt[0](&hooked), other.vmt[0](&other)), (2, 1));
+///
+/// drop(shadow);
+/// assert_eq!(hooked.vmt[0](&hooked), 1);
+/// ```
+pub struct ShadowVmt {
+    object: *mut VmtPtr,
+    original: VmtPtr,
+    /// Copied prefix followed by the copied entries.
+    table: Box<[usize]>,
+    prefix: usize,
+}
+
+impl ShadowVmt {
+    /// Words before the entries used for RTTI by the compilers of the target,
+    /// the complete object locator with MSVC, offset to top and type info with the Itanium ABI.
+    pub const RTTI_PREFIX: usize = if cfg!(windows) { 1 } else { 2 };
+
+    /// Copies `prefix` words before the table of `object` and `len` entries of the table,
+    /// then makes the object use the copy.
+    /// # Safety
+    /// * The first field of `object` must be a pointer to a virtual method table with
+    ///   at least `len` entries, preceded by at least `prefix` readable words.
+    /// * `object` must stay valid until the shadow is dropped.
+    pub unsafe fn new<T>(object: *mut T, prefix: usize, len: usize) -> Self {
+        let object = object.cast::<VmtPtr>();
+        let original = VmtPtr { vmt: (*object).vmt };
+        let start = original.vmt.sub(prefix);
+        let table = Box::<[usize]>::from(core::slice::from_raw_parts(start, prefix + len));
+        (*object).vmt = table.as_ptr().add(prefix);
+
+        Self {
+            object,
+            original,
+            table,
+            prefix,
+        }
+    }
+
+    /// Amount of entries in the copy.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.table.len() - self.prefix
+    }
+
+    /// Checks if the copy has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Amount of words copied before the entries.
+    #[inline]
+    pub fn prefix(&self) -> usize {
+        self.prefix
+    }
+
+    /// Original table of the object.
+    #[inline]
+    pub fn original_table(&self) -> &VmtPtr {
+        &self.original
+    }
+
+    /// Replaces the function of `entry` in the copy, returning the original function.
+    /// # Panics
+    /// * If the entry isn't in the copy.
+    /// # Safety
+    /// * `F` must be a function pointer type.
+    pub unsafe fn hook<F: Copy>(&mut self, entry: VmtEntry<F>, detour: F) -> F {
+        self.table[self.prefix + entry.index()] = transmute_copy(&detour);
+        self.original(entry)
+    }
+
+    /// Function of `entry` in the original table.
+    /// # Panics
+    /// * If the entry isn't in the copy.
+    pub fn original<F: Copy>(&self, entry: VmtEntry<F>) -> F {
+        unsafe { transmute_copy(&self.original_at(entry.index())) }
+    }
+
+    /// Puts the original function of `entry` back into the copy.
+    /// # Panics
+    /// * If the entry isn't in the copy.
+    pub fn unhook<F>(&mut self, entry: VmtEntry<F>) {
+        self.table[self.prefix + entry.index()] = self.original_at(entry.index());
+    }
+
+    fn original_at(&self, index: usize) -> usize {
+        assert!(index < self.len(), "Entry is out of the table");
+        unsafe { self.original.at(index) }
+    }
+}
+
+impl Drop for ShadowVmt {
+    fn drop(&mut self) {
+        unsafe {
+            // Object could have been given another table since.
+            if (*self.object).vmt == self.table.as_ptr().add(self.prefix) {
+                (*self.object).vmt = self.original.vmt;
+            }
+        }
+    }
+}
@@ -1,8 +1,19 @@
+use crate::{
+    types::{MemoryRegion, ModuleInfoWithName, Protection},
+    MemorySink, MemorySource, MfError,
+};
+use core::mem::size_of;
 use windows::Win32::{
     Foundation::HINSTANCE,
     System::{
         Console::{AllocConsole, FreeConsole},
+        Diagnostics::Debug::ReadProcessMemory,
         LibraryLoader::FreeLibraryAndExitThread,
+        Memory::{
+            VirtualAlloc, VirtualFree, VirtualProtect, VirtualQuery, MEMORY_BASIC_INFORMATION,
+            MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_NOACCESS, PAGE_PROTECTION_FLAGS,
+        },
+        Threading::GetCurrentProcess,
     },
 };
 
@@ -55,6 +66,53 @@ pub fn modules() -> impl Iterator<Item = crate::types::ModuleInfoWithName> {
     }
 }
 
+/// Searches for every pattern of the set in the specified module in a single pass,
+/// returning ids of the patterns with their matches.
+pub fn find_pattern_set_in_module<'a>(
+    set: &'a crate::PatternSet,
+    module_name: &str,
+) -> Option<impl Iterator<Item = (usize, crate::Match)> + 'a> {
+    let module = find_module_by_name(module_name)?;
+    unsafe { Some(crate::find_pattern_set(set, module.base, module.size)) }
+}
+
+/// Creates a signature for the code at `address`, growing it until it is unique
+/// within the module that contains the address.
+/// Returns `None` if the address doesn't belong to any module.
+/// # Behavior
+/// Relative branch targets, RIP-relative displacements and operands
+/// that point into the module are wildcarded.
+/// The pattern won't be longer than `max_len` bytes.
+pub fn make_signature(address: *const u8, max_len: usize) -> Option<crate::Signature> {
+    let address = address as usize;
+    let module =
+        modules().find(|m| (m.base as usize..m.base as usize + m.size).contains(&address))?;
+    let range = module.base as usize..module.base as usize + module.size;
+    let data = unsafe { core::slice::from_raw_parts(module.base, module.size) };
+
+    let image = crate::Image {
+        segments: vec![(range.start, data)],
+        range,
+    };
+    crate::make_signature(&image, address, max_len)
+}
+
+/// Checks every pattern of the checker against a loaded module.
+/// Returns [`crate::MfError::ModuleNotFound`] if `module` doesn't describe a loaded module.
+/// # Behavior
+/// The whole image is scanned, from the base up to its `SizeOfImage`.
+pub fn check_signatures(
+    checker: &crate::SignatureChecker,
+    module: &crate::types::ModuleInfo,
+) -> crate::Result<crate::SignatureReport> {
+    if !modules().any(|m| m.base == module.base && m.size == module.size) {
+        return Err(crate::MfError::ModuleNotFound);
+    }
+
+    let found = unsafe { crate::find_pattern_set(checker.patterns(), module.base, module.size) };
+    Ok(checker.report_matches(module.base as usize, found))
+}
+
 /// Allocates new console.
 pub fn alloc_console() -> bool {
     unsafe { AllocConsole().as_bool() }
@@ -69,3 +127,108 @@ pub fn free_console() -> bool {
 pub fn free_library_and_exit_thread(lib: usize, code: u32) -> ! {
     unsafe { FreeLibraryAndExitThread(HINSTANCE(lib as _), code) }
 }
+
+/// Changes the protection of a memory region
+pub fn protect(address: usize, len: usize, prot: Protection) -> crate::Result<()> {
+    let mut old = PAGE_PROTECTION_FLAGS(0);
+    unsafe {
+        if VirtualProtect(address as _, len, prot.to_os(), &mut old).as_bool() {
+            Ok(())
+        } else {
+            MfError::last()
+        }
+    }
+}
+
+/// Allocates virtual memory
+pub fn allocate(address: Option<usize>, len: usize, prot: Protection) -> crate::Result<*mut u8> {
+    unsafe {
+        let addr = VirtualAlloc(
+            Some(address.unwrap_or(0) as _),
+            len,
+            MEM_COMMIT | MEM_RESERVE,
+            prot.to_os(),
+        );
+
+        if addr.is_null() {
+            MfError::last()
+        } else {
+            Ok(addr as _)
+        }
+    }
+}
+
+/// Frees virtual memory
+/// # Windows
+/// The whole allocation is released, `len` is ignored.
+pub fn free(address: usize, _len: usize) -> crate::Result<()> {
+    unsafe {
+        if VirtualFree(address as _, 0, MEM_RELEASE).as_bool() {
+            Ok(())
+        } else {
+            MfError::last()
+        }
+    }
+}
+
+impl MemorySource for super::CurrentProcess {
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        let mut read = 0;
+        unsafe {
+            if ReadProcessMemory(
+                GetCurrentProcess(),
+                address as _,
+                buf.as_mut_ptr() as _,
+                buf.len() as _,
+                Some(&mut read as _),
+            )
+            .as_bool()
+            {
+                Ok(read)
+            } else {
+                MfError::last()
+            }
+        }
+    }
+
+    fn modules(&self) -> crate::Result<Vec<ModuleInfoWithName>> {
+        Ok(modules().collect())
+    }
+
+    fn maps(&self) -> crate::Result<Vec<MemoryRegion>> {
+        let mut maps = vec![];
+
+        unsafe {
+            let mut address = 0;
+            let mut info = MEMORY_BASIC_INFORMATION::default();
+            while VirtualQuery(
+                Some(address as _),
+                &mut info,
+                size_of::<MEMORY_BASIC_INFORMATION>(),
+            ) > 0
+            {
+                address = info.BaseAddress as usize + info.RegionSize;
+                if info.State != MEM_COMMIT || info.Protect == PAGE_NOACCESS {
+                    continue;
+                }
+
+                maps.push(MemoryRegion {
+                    from: info.BaseAddress as _,
+                    to: address,
+                    prot: Protection::from_os(info.Protect).unwrap_or_default(),
+                });
+            }
+        }
+
+        Ok(maps)
+    }
+}
+
+impl MemorySink for super::CurrentProcess {
+    /// Adds write permission to the memory for the duration of the write.
+    fn write_buf(&self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        let _guard = super::ProtectionGuard::writable(address, buf.len())?;
+        unsafe { core::ptr::copy(buf.as_ptr(), address as *mut u8, buf.len()) };
+        Ok(buf.len())
+    }
+}
@@ -9,6 +9,30 @@ pub use macros::*;
 mod pattern;
 pub use pattern::*;
 
+#[cfg(any(feature = "internal", feature = "external"))]
+mod x86;
+
+#[cfg(any(feature = "internal", feature = "external"))]
+mod source;
+#[cfg(any(feature = "internal", feature = "external"))]
+pub use source::*;
+#[cfg(any(feature = "internal", feature = "external"))]
+mod pointer;
+#[cfg(any(feature = "internal", feature = "external"))]
+pub use pointer::*;
+#[cfg(any(feature = "internal", feature = "external"))]
+mod value;
+#[cfg(any(feature = "internal", feature = "external"))]
+pub use value::*;
+#[cfg(any(feature = "internal", feature = "external"))]
+mod snapshot;
+#[cfg(any(feature = "internal", feature = "external"))]
+pub use snapshot::*;
+#[cfg(any(feature = "internal", feature = "external"))]
+mod patch;
+#[cfg(any(feature = "internal", feature = "external"))]
+pub use patch::*;
+
 #[cfg(feature = "internal")]
 /// Module with helper functions for internal apis.
 pub mod internal;
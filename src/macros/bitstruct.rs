@@ -57,13 +57,15 @@ macro_rules! bitstruct {
                 $(
                     $crate::paste! {
                         $fvs fn [<$fname _mut >](&mut self) -> $crate::BitFieldMut<'_, $int, {$from % 8}, {$to - $from + 1}> {
-                            let ptr = unsafe { self.0.get().cast::<u8>().add($from / 8) };
+                            let offset = $from / 8;
+                            let ptr = unsafe { self.0.get().cast::<u8>().add(offset) };
                             unsafe { $crate::BitFieldMut::from_ptr(ptr) }
                         }
                     }
 
                     $fvs fn $fname(&self) -> $crate::BitField<'_, $int, {$from % 8}, {$to - $from + 1}> {
-                        let ptr = unsafe { self.0.get().cast::<u8>().add($from / 8) };
+                        let offset = $from / 8;
+                        let ptr = unsafe { self.0.get().cast::<u8>().add(offset) };
                         unsafe { $crate::BitField::from_ptr(ptr) }
                     }
                 )*
@@ -133,7 +135,7 @@ macro_rules! bitfields {
             impl $target {
                 $(
                     $crate::paste! {
-                        $fvs fn [< $fname _mut >](&mut self) -> $crate::BitFieldMut<$int, {$from % 8}, {$to - $from + 1}> {
+                        $fvs fn [< $fname _mut >](&mut self) -> $crate::BitFieldMut<'_, $int, {$from % 8}, {$to - $from + 1}> {
                             let x = if $from % 8 == 0 && $from != 0 {
                                 $from / 8 + 1
                             } else {
@@ -147,7 +149,7 @@ macro_rules! bitfields {
 
                     }
 
-                    $fvs fn $fname(&self) -> $crate::BitField<$int, {$from % 8}, {$to - $from + 1}> {
+                    $fvs fn $fname(&self) -> $crate::BitField<'_, $int, {$from % 8}, {$to - $from + 1}> {
                         let x = if $from % 8 == 0 && $from != 0 {
                             $from / 8 + 1
                         } else {
@@ -251,7 +253,7 @@ impl<I: BitInteger, const O: usize> BitFieldMut<'_, I, O, 1> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{bitstruct, BitFieldMut};
+    use crate::BitFieldMut;
 
     bitstruct! {
         pub struct Foo : u16 {
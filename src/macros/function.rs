@@ -43,6 +43,8 @@ macro_rules! function {
 pub struct Function<F> {
     address: AtomicUsize,
     init: fn() -> usize,
+    /// Trampoline of the hook, zero if the function isn't hooked.
+    original: AtomicUsize,
     _ph: PhantomData<F>,
 }
 
@@ -52,6 +54,7 @@ impl<F> Function<F> {
         Self {
             address: AtomicUsize::new(0),
             init,
+            original: AtomicUsize::new(0),
             _ph: PhantomData,
         }
     }
@@ -71,6 +74,90 @@ impl<F> Function<F> {
     pub fn force(&self) {
         _ = self.address();
     }
+
+    /// Returns the function that bypasses the hook created with [`Function::hook`],
+    /// or the function itself if it isn't hooked.
+    pub fn call_original(&self) -> F
+    where
+        F: Copy,
+    {
+        match self.original.load(Ordering::Acquire) {
+            0 => *self.deref(),
+            original => unsafe { core::mem::transmute_copy(&original) },
+        }
+    }
+}
+
+#[cfg(all(feature = "internal", target_arch = "x86_64"))]
+impl<F: Copy> Function<F> {
+    /// Redirects calls of the function to `detour`, the original function is available
+    /// with [`Function::call_original`] until the returned handle is dropped.
+    /// ```
+    /// # use memflex::ResolveBy;
+    /// #[inline(never)]
+    /// extern "C" fn add(a: i32, b: i32) -> i32 {
+    ///     a + b
+    /// }
+    ///
+    /// fn resolve_add<const N: usize>(_: ResolveBy<N>) -> usize {
+    ///     add as *const () as usize
+    /// }
+    ///
+    /// memflex::function! {
+    ///     extern "C" fn ADD(i32, i32) -> i32 = (resolve_add)"add"#0;
+    /// }
+    ///
+    /// extern "C" fn add_twice(a: i32, b: i32) -> i32 {
+    ///     ADD.call_original()(a, b) * 2
+    /// }
+    ///
+    /// let hook = unsafe { ADD.hook(add_twice) }.unwrap();
+    /// assert_eq!(ADD(1, 2), 6);
+    /// drop(hook);
+    /// assert_eq!(ADD(1, 2), 3);
+    /// ```
+    /// # Errors
+    /// * [`crate::MfError::PatchOverlap`] with the address of the function if it's already hooked.
+    /// * Errors of [`crate::internal::InlineHook::new`] and [`crate::internal::InlineHook::enable`].
+    /// # Safety
+    /// * `F` must be a function pointer.
+    /// * Same requirements as [`crate::internal::InlineHook::new`].
+    pub unsafe fn hook(&'static self, detour: F) -> crate::Result<HookHandle<F>> {
+        let target = self.address();
+        if self.original.load(Ordering::Acquire) != 0 {
+            return Err(crate::MfError::PatchOverlap(target));
+        }
+
+        let detour = core::mem::transmute_copy::<F, usize>(&detour);
+        let hook = crate::internal::InlineHook::new(target as _, detour as _)?;
+        if self
+            .original
+            .compare_exchange(
+                0,
+                hook.trampoline() as usize,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_err()
+        {
+            return Err(crate::MfError::PatchOverlap(target));
+        }
+
+        // Handle clears the trampoline if enabling fails.
+        let mut handle = HookHandle {
+            function: self,
+            hook,
+        };
+        handle.enable()?;
+        Ok(handle)
+    }
+}
+
+/// Hook of a [`Function`], disabled when dropped.
+#[cfg(all(feature = "internal", target_arch = "x86_64"))]
+pub struct HookHandle<F: 'static> {
+    function: &'static Function<F>,
+    hook: crate::internal::InlineHook,
 }
 
 impl<F> Deref for Function<F> {
@@ -87,3 +174,56 @@ impl<F> Deref for Function<F> {
         }
     }
 }
+
+#[cfg(all(feature = "internal", target_arch = "x86_64"))]
+impl<F: Copy> HookHandle<F> {
+    /// The hooked function.
+    #[inline]
+    pub fn function(&self) -> &'static Function<F> {
+        self.function
+    }
+
+    /// Function that bypasses the hook.
+    #[inline]
+    pub fn original(&self) -> F {
+        unsafe { core::mem::transmute_copy(&self.hook.trampoline()) }
+    }
+
+    /// Checks if calls of the function are redirected.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.hook.is_enabled()
+    }
+
+    /// Redirects calls of the function to the detour again.
+    /// # Safety
+    /// * Same requirements as [`crate::internal::InlineHook::enable`].
+    pub unsafe fn enable(&mut self) -> crate::Result<()> {
+        self.hook.enable()
+    }
+
+    /// Calls of the function go to the original code.
+    /// [`Function::call_original`] keeps working until the handle is dropped.
+    /// # Safety
+    /// * Same requirements as [`crate::internal::InlineHook::disable`].
+    pub unsafe fn disable(&mut self) -> crate::Result<()> {
+        self.hook.disable()
+    }
+
+    /// Removes the hook, returning the error if the original code can't be written back.
+    /// # Safety
+    /// * Same requirements as [`crate::internal::InlineHook::unhook`].
+    pub unsafe fn unhook(mut self) -> crate::Result<()> {
+        self.disable()
+    }
+}
+
+#[cfg(all(feature = "internal", target_arch = "x86_64"))]
+impl<F: 'static> Drop for HookHandle<F> {
+    fn drop(&mut self) {
+        // Trampoline stays in use while the function still jumps to the detour.
+        if unsafe { self.hook.disable() }.is_ok() {
+            self.function.original.store(0, Ordering::Release);
+        }
+    }
+}
@@ -1,4 +1,7 @@
 /// Generates a trait that will emulate behaviour of C++ virtual functions
+///
+/// Every virtual function also gets a [`crate::types::VmtEntry`] constant named after it
+/// in upper case, used to hook the function with its signature.
 /// # Safety
 /// Although functions generated by this macro are not marked as unsafe, they will
 /// cause access violation if called on invalid objects.
@@ -44,6 +47,14 @@ macro_rules! interface {
                 const FUNCTION_COUNT: usize = $( $crate::__count_fns!($fname) + )*0;
 
                 $(
+                    $crate::__gen_entry! {
+                        $sep
+                        [$idx],
+                        $(extern $($abi)?)?,
+                        $fname,
+                        ($($arg_ty),* ),
+                        $($ret)?
+                    }
 
                     $crate::__gen_func! {
                         $sep
@@ -96,6 +107,20 @@ macro_rules! __gen_func {
     };
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __gen_entry {
+    (# [$idx:expr], $(extern $($abi:literal)?)?, $fname:ident, ( $($arg_ty:ty),* ), $($ret:ty)? ) => {
+        $crate::paste! {
+            #[doc = concat!("Virtual method table entry of [`Self::", stringify!($fname), "`].")]
+            const [<$fname:upper>]: $crate::types::VmtEntry<
+                for<'this> $(extern $($abi)?)? fn(&'this Self, $($arg_ty),*) $(-> $ret)?
+            > = $crate::types::VmtEntry::new(Self::INDEX_OFFSET + $idx);
+        }
+    };
+    (% [$idx:expr], $($rest:tt)*) => {};
+}
+
 #[macro_export]
 #[doc(hidden)]
 macro_rules! __count_fns {
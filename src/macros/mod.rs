@@ -43,7 +43,7 @@ macro_rules! __resolve_by {
 }
 
 #[doc(hidden)]
-#[cfg(all(windows, feature = "std"))]
+#[cfg(any(all(windows, feature = "std"), all(unix, feature = "internal")))]
 pub fn __default_resolver<const N: usize>(res: ResolveBy<N>) -> usize {
     use crate::internal::{find_module_by_name, find_pattern_in_module};
 
@@ -68,7 +68,7 @@ pub fn __default_resolver<const N: usize>(res: ResolveBy<N>) -> usize {
 }
 
 #[doc(hidden)]
-#[cfg(not(windows))]
+#[cfg(not(any(all(windows, feature = "std"), all(unix, feature = "internal"))))]
 pub fn __default_resolver<const N: usize>(_: ResolveBy<N>) -> usize {
     unimplemented!()
 }
@@ -1,4 +1,6 @@
-use crate::Matcher;
+#[cfg(feature = "alloc")]
+use crate::PatternSet;
+use crate::{Match, Matcher, ScanOptions, Scanner};
 use core::{ops::RangeInclusive, slice::from_raw_parts};
 
 /// Creates an inmmutable slice from terminated array.
@@ -90,19 +92,99 @@ pub unsafe fn find_pattern(
     pat: impl Matcher,
     start: *const u8,
     len: usize,
+) -> impl Iterator<Item = *const u8> {
+    find_pattern_with(pat, start, len, ScanOptions::default())
+}
+
+/// Searches for a pattern internally by start address and search length,
+/// with control over direction, amount and alignment of the matches.
+/// # Safety
+/// * `start` is a valid pointer and can be read
+/// * Memory from `start` to `start + len` (inclusive) can be read
+/// ```
+/// # use memflex::{ida_pat, ScanOptions};
+/// #[repr(align(16))]
+/// struct Aligned([u8; 6]);
+///
+/// let data = Aligned(*b"\x90\xC3\x90\x90\xC3\x90");
+/// let options = ScanOptions { reverse: true, align: 2, ..Default::default() };
+/// # unsafe {
+/// let found = memflex::find_pattern_with(ida_pat!("90"), data.0.as_ptr(), 6, options)
+///     .map(|p| p.offset_from(data.0.as_ptr()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(found, [2, 0]);
+/// # }
+/// ```
+pub unsafe fn find_pattern_with(
+    pat: impl Matcher,
+    start: *const u8,
+    len: usize,
+    options: ScanOptions,
 ) -> impl Iterator<Item = *const u8> {
     assert!(!start.is_null());
 
-    from_raw_parts::<u8>(start, len)
-        .windows(pat.len())
-        .enumerate()
-        .filter_map(move |(i, bytes)| {
-            if pat.matches(bytes) {
-                Some(start.add(i))
-            } else {
-                None
-            }
-        })
+    let data = from_raw_parts::<u8>(start, len);
+    let scanner = Scanner::new(pat);
+    let (mut lo, mut hi) = (0, len);
+
+    core::iter::from_fn(move || {
+        let found = scanner.search(
+            data,
+            lo..hi,
+            true,
+            options.reverse,
+            start as usize,
+            options.stride(),
+        )?;
+        if options.reverse {
+            hi = found;
+        } else {
+            lo = found + 1;
+        }
+        Some(start.add(found))
+    })
+    .take(options.limit())
+}
+
+/// Searches for a pattern internally by start address and search length,
+/// returning matches with their capture slots.
+/// # Safety
+/// * `start` is a valid pointer and can be read
+/// * Memory from `start` to `start + len` (inclusive) can be read
+#[inline]
+pub unsafe fn find_matches(
+    pat: impl Matcher,
+    start: *const u8,
+    len: usize,
+) -> impl Iterator<Item = Match> {
+    let m = Match::new(&pat, 0);
+    find_pattern(pat, start, len).map(move |a| m.at(a as usize))
+}
+
+/// Searches for every pattern of the set internally in a single pass,
+/// returning ids of the patterns with their matches.
+/// # Safety
+/// * `start` is a valid pointer and can be read
+/// * Memory from `start` to `start + len` (inclusive) can be read
+/// ```
+/// # use memflex::{ida_pat, PatternSet};
+/// let mut set = PatternSet::new();
+/// set.add(ida_pat!("22 33"));
+/// let data = b"\x11\x22\x33";
+/// # unsafe {
+/// let (id, m) = memflex::find_pattern_set(&set, data.as_ptr(), data.len()).next().unwrap();
+/// assert_eq!((id, m.address()), (0, data.as_ptr().add(1) as usize));
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+pub unsafe fn find_pattern_set(
+    set: &PatternSet,
+    start: *const u8,
+    len: usize,
+) -> impl Iterator<Item = (usize, Match)> + '_ {
+    let data = from_raw_parts(start, len);
+    set.scan(data)
+        .map(move |(id, offset)| (id, set.match_at(id, start as usize + offset)))
 }
 
 /// Searches for a pattern internally in a given range.
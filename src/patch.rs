@@ -0,0 +1,258 @@
+extern crate alloc;
+use crate::{MemorySink, MfError};
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+/// Bytes to write over memory of a process, see [`Patch::apply`] and [`PatchSet`].
+/// ```
+/// # use memflex::{internal::CurrentProcess, Patch};
+/// let mut code = Box::new(*b"\x74\x05\xC3");
+/// let address = code.as_mut_ptr() as usize;
+///
+/// let patch = Patch::nop(address, 2).expect(*b"\x74\x05").apply(&CurrentProcess).unwrap();
+/// assert_eq!(*code, *b"\x90\x90\xC3");
+///
+/// patch.restore().unwrap();
+/// assert_eq!(*code, *b"\x74\x05\xC3");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    /// Address the bytes are written at.
+    pub address: usize,
+    /// Bytes to write.
+    pub bytes: Vec<u8>,
+    /// Bytes the memory has to contain before the patch is applied.
+    pub expected: Option<Vec<u8>>,
+}
+
+impl Patch {
+    /// Writes `bytes` at `address`.
+    pub fn new(address: usize, bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            address,
+            bytes: bytes.into(),
+            expected: None,
+        }
+    }
+
+    /// Replaces `len` bytes at `address` with `nop` instructions.
+    pub fn nop(address: usize, len: usize) -> Self {
+        Self::new(address, vec![0x90; len])
+    }
+
+    /// Replaces the code at `address` with a jump to `destination`.
+    /// # Behavior
+    /// Uses a 5 byte relative jump if `destination` is within 2GB, otherwise
+    /// a 14 byte `jmp [rip]` followed by the absolute address.
+    #[cfg(target_arch = "x86_64")]
+    pub fn jump(address: usize, destination: usize) -> Self {
+        let rel = destination.wrapping_sub(address + 5) as isize;
+        let bytes = if let Ok(rel) = i32::try_from(rel) {
+            [&[0xE9][..], &rel.to_le_bytes()].concat()
+        } else {
+            [
+                &[0xFF, 0x25, 0, 0, 0, 0][..],
+                &(destination as u64).to_le_bytes(),
+            ]
+            .concat()
+        };
+
+        Self::new(address, bytes)
+    }
+
+    /// Requires the memory to contain `original` before the patch is applied.
+    pub fn expect(mut self, original: impl Into<Vec<u8>>) -> Self {
+        self.expected = Some(original.into());
+        self
+    }
+
+    /// Addresses covered by the patch.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.address..self.address + self.bytes.len()
+    }
+
+    /// Writes the patch, remembering the bytes it replaces.
+    /// Original bytes are restored when the returned value is dropped.
+    /// # Errors
+    /// * [`MfError::UnexpectedBytes`] if the memory doesn't contain the expected bytes.
+    /// * [`MfError::InvalidAddress`] if the memory can't be read or written.
+    pub fn apply<S: MemorySink + ?Sized>(self, target: &S) -> crate::Result<AppliedPatch<'_, S>> {
+        let expected = self.expected.as_deref().unwrap_or_default();
+        let mut original = vec![0; self.bytes.len().max(expected.len())];
+        if target.read_buf(self.address, &mut original).ok() != Some(original.len()) {
+            return Err(MfError::InvalidAddress(self.address));
+        }
+
+        if !original.starts_with(expected) {
+            return Err(MfError::UnexpectedBytes(self.address));
+        }
+        original.truncate(self.bytes.len());
+
+        write_all(target, self.address, &self.bytes)?;
+        Ok(AppliedPatch {
+            target,
+            patch: self,
+            original,
+            applied: true,
+        })
+    }
+}
+
+/// Writes the whole buffer, [`MfError::InvalidAddress`] on partial writes.
+fn write_all<S: MemorySink + ?Sized>(target: &S, address: usize, buf: &[u8]) -> crate::Result<()> {
+    match target.write_buf(address, buf)? {
+        n if n == buf.len() => Ok(()),
+        n => Err(MfError::InvalidAddress(address + n)),
+    }
+}
+
+/// Patch written to memory of a process, restores the original bytes when dropped.
+#[must_use]
+pub struct AppliedPatch<'a, S: MemorySink + ?Sized> {
+    target: &'a S,
+    patch: Patch,
+    original: Vec<u8>,
+    applied: bool,
+}
+
+impl<S: MemorySink + ?Sized> AppliedPatch<'_, S> {
+    /// The patch.
+    #[inline]
+    pub fn patch(&self) -> &Patch {
+        &self.patch
+    }
+
+    /// Bytes the patch replaced.
+    #[inline]
+    pub fn original(&self) -> &[u8] {
+        &self.original
+    }
+
+    /// Checks if the patched bytes are currently in memory.
+    #[inline]
+    pub fn is_applied(&self) -> bool {
+        self.applied
+    }
+
+    /// Writes the original bytes back, the patch can be applied again with [`AppliedPatch::enable`].
+    pub fn disable(&mut self) -> crate::Result<()> {
+        if self.applied {
+            write_all(self.target, self.patch.address, &self.original)?;
+            self.applied = false;
+        }
+        Ok(())
+    }
+
+    /// Writes the patched bytes again after [`AppliedPatch::disable`].
+    pub fn enable(&mut self) -> crate::Result<()> {
+        if !self.applied {
+            write_all(self.target, self.patch.address, &self.patch.bytes)?;
+            self.applied = true;
+        }
+        Ok(())
+    }
+
+    /// Writes the original bytes back now, returning the error if it fails.
+    pub fn restore(mut self) -> crate::Result<()> {
+        self.disable()
+    }
+}
+
+impl<S: MemorySink + ?Sized> Drop for AppliedPatch<'_, S> {
+    fn drop(&mut self) {
+        _ = self.disable();
+    }
+}
+
+/// Group of patches that don't overlap, restored together when dropped.
+/// ```
+/// # use memflex::{internal::CurrentProcess, MfError, Patch, PatchSet};
+/// let mut data = Box::new([0_u8; 8]);
+/// let address = data.as_mut_ptr() as usize;
+/// {
+///     let mut set = PatchSet::new(&CurrentProcess);
+///     set.apply(Patch::new(address, [1, 2])).unwrap();
+///     set.apply(Patch::new(address + 4, [3])).unwrap();
+///     assert!(matches!(
+///         set.apply(Patch::new(address + 1, [4])),
+///         Err(MfError::PatchOverlap(a)) if a == address
+///     ));
+///     assert_eq!(*data, [1, 2, 0, 0, 3, 0, 0, 0]);
+/// }
+/// assert_eq!(*data, [0; 8]);
+/// ```
+pub struct PatchSet<'a, S: MemorySink + ?Sized> {
+    target: &'a S,
+    patches: Vec<AppliedPatch<'a, S>>,
+}
+
+impl<'a, S: MemorySink + ?Sized> PatchSet<'a, S> {
+    /// Creates an empty set that patches memory of `target`.
+    pub fn new(target: &'a S) -> Self {
+        Self {
+            target,
+            patches: Vec::new(),
+        }
+    }
+
+    /// Applies the patch, returning its index in the set.
+    /// # Errors
+    /// * [`MfError::PatchOverlap`] with the address of the applied patch it overlaps.
+    /// * Errors of [`Patch::apply`].
+    pub fn apply(&mut self, patch: Patch) -> crate::Result<usize> {
+        let range = patch.range();
+        if let Some(other) = self.patches.iter().find(|p| {
+            let other = p.patch.range();
+            other.start < range.end && range.start < other.end
+        }) {
+            return Err(MfError::PatchOverlap(other.patch.address));
+        }
+
+        self.patches.push(patch.apply(self.target)?);
+        Ok(self.patches.len() - 1)
+    }
+
+    /// Applied patches, in the order they were applied.
+    #[inline]
+    pub fn patches(&self) -> &[AppliedPatch<'a, S>] {
+        &self.patches
+    }
+
+    /// Patch at `index`.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut AppliedPatch<'a, S>> {
+        self.patches.get_mut(index)
+    }
+
+    /// Amount of patches.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.patches.len()
+    }
+
+    /// Checks if the set has no patches.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    /// Restores every patch in reverse order, returning the first error.
+    /// Patches that couldn't be restored are kept in the set.
+    pub fn restore(&mut self) -> crate::Result<()> {
+        let mut result = Ok(());
+        for i in (0..self.patches.len()).rev() {
+            match self.patches[i].disable() {
+                Ok(_) => drop(self.patches.remove(i)),
+                Err(e) => result = result.and(Err(e)),
+            }
+        }
+        result
+    }
+}
+
+impl<S: MemorySink + ?Sized> Drop for PatchSet<'_, S> {
+    fn drop(&mut self) {
+        _ = self.restore();
+    }
+}
@@ -0,0 +1,139 @@
+use crate::Matcher;
+
+/// Maximum amount of capture slots in a single pattern.
+pub const MAX_CAPTURES: usize = 8;
+
+/// Offsets of capture slots from the start of a pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Captures {
+    offsets: [usize; MAX_CAPTURES],
+    len: usize,
+}
+
+impl Captures {
+    pub const fn new() -> Self {
+        Self {
+            offsets: [0; MAX_CAPTURES],
+            len: 0,
+        }
+    }
+
+    /// Adds new slot at `offset`, `None` if there are already [`MAX_CAPTURES`] slots.
+    pub const fn with(mut self, offset: usize) -> Option<Self> {
+        if self.len == MAX_CAPTURES {
+            return None;
+        }
+
+        self.offsets[self.len] = offset;
+        self.len += 1;
+        Some(self)
+    }
+
+    /// Copies `offsets` into the slots.
+    /// # Panics
+    /// If there are more than [`MAX_CAPTURES`] offsets.
+    pub fn from_slice(offsets: &[usize]) -> Self {
+        assert!(
+            offsets.len() <= MAX_CAPTURES,
+            "pattern has more than MAX_CAPTURES capture slots"
+        );
+
+        let mut captures = Self::new();
+        captures.offsets[..offsets.len()].copy_from_slice(offsets);
+        captures.len = offsets.len();
+        captures
+    }
+
+    pub fn as_slice(&self) -> &[usize] {
+        &self.offsets[..self.len]
+    }
+}
+
+/// Single occurence of a pattern together with the offsets of its capture slots.
+/// Capture slots are marked with `&` in IDA and PEID style patterns.
+/// ```
+/// # use memflex::{find_matches, ida_pat};
+/// // lea rax, [rip + 0x10]
+/// let code = [0x48, 0x8D, 0x05, 0x10, 0x00, 0x00, 0x00];
+///
+/// let m = unsafe {
+///     find_matches(ida_pat!("48 8D 05 & ? ? ? ?"), code.as_ptr(), code.len())
+///         .next()
+///         .unwrap()
+/// };
+///
+/// assert_eq!(m.capture(0), code.as_ptr() as usize + 3);
+/// assert_eq!(unsafe { m.read::<i32>(0) }, 0x10);
+/// assert_eq!(unsafe { m.rip_relative(0, 7) }, code.as_ptr() as usize + 7 + 0x10);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    address: usize,
+    captures: Captures,
+}
+
+impl Match {
+    /// Creates new match of `pat` at `address`.
+    /// # Panics
+    /// If `pat` has more than [`MAX_CAPTURES`] capture slots.
+    pub fn new(pat: &(impl Matcher + ?Sized), address: usize) -> Self {
+        Self {
+            address,
+            captures: Captures::from_slice(pat.captures()),
+        }
+    }
+
+    /// Moves the match to `address`, keeping capture slots.
+    #[inline]
+    pub(crate) fn at(self, address: usize) -> Self {
+        Self { address, ..self }
+    }
+
+    /// Address of the first byte of the match.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Amount of capture slots.
+    #[inline]
+    pub fn captures(&self) -> usize {
+        self.captures.len
+    }
+
+    /// Returns the address of the capture slot.
+    /// # Panics
+    /// If the pattern has less than `slot + 1` capture slots.
+    #[inline]
+    pub fn capture(&self, slot: usize) -> usize {
+        self.address + self.captures.as_slice()[slot]
+    }
+
+    /// Reads value of type `T` at the capture slot.
+    /// # Safety
+    /// * Capture slot must be valid for reading `T`.
+    /// # Panics
+    /// If the pattern has less than `slot + 1` capture slots.
+    #[inline]
+    pub unsafe fn read<T>(&self, slot: usize) -> T {
+        (self.capture(slot) as *const T).read_unaligned()
+    }
+
+    /// Reads `i32` displacement at the capture slot and resolves it relative to the end of
+    /// the instruction, `insn_end` being its offset from the start of the match.
+    /// # Safety
+    /// * Capture slot must be valid for reading `i32`.
+    /// # Panics
+    /// If the pattern has less than `slot + 1` capture slots.
+    #[inline]
+    pub unsafe fn rip_relative(&self, slot: usize, insn_end: usize) -> usize {
+        self.resolve_rip_relative(self.read(slot), insn_end)
+    }
+
+    /// Resolves displacement `disp` relative to the end of the instruction,
+    /// `insn_end` being its offset from the start of the match.
+    #[inline]
+    pub fn resolve_rip_relative(&self, disp: i32, insn_end: usize) -> usize {
+        (self.address + insn_end).wrapping_add_signed(disp as isize)
+    }
+}
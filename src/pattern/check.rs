@@ -0,0 +1,134 @@
+extern crate alloc;
+use crate::{Matcher, PatternSet};
+use alloc::{string::String, vec, vec::Vec};
+
+/// Named patterns whose health is checked against a module.
+/// ```
+/// # use memflex::{ida_pat, SignatureChecker, SignatureStatus};
+/// let mut checker = SignatureChecker::new();
+/// checker.add("first", ida_pat!("11 ? 33"));
+/// checker.add("second", ida_pat!("22"));
+/// checker.add("third", ida_pat!("55"));
+///
+/// let data = b"\x11\x22\x33\x22";
+/// let report = checker.check(data);
+/// assert_eq!(report.get("first").unwrap().status(), SignatureStatus::Unique);
+/// assert_eq!(report.get("second").unwrap().offsets, [1, 3]);
+/// assert_eq!(report.broken().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["second", "third"]);
+/// ```
+#[derive(Default)]
+pub struct SignatureChecker {
+    names: Vec<String>,
+    set: PatternSet,
+}
+
+impl SignatureChecker {
+    /// Creates an empty checker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a named pattern.
+    pub fn add(&mut self, name: impl Into<String>, pat: impl Matcher + Send + Sync + 'static) {
+        self.names.push(name.into());
+        self.set.add(pat);
+    }
+
+    /// Patterns being checked, ids are the order they were added in.
+    pub fn patterns(&self) -> &PatternSet {
+        &self.set
+    }
+
+    /// Checks patterns against a byte slice, offsets are relative to its start.
+    pub fn check(&self, data: &[u8]) -> SignatureReport {
+        self.report(self.set.scan(data))
+    }
+
+    /// Builds a report from `(id, offset)` pairs.
+    pub(crate) fn report(&self, found: impl Iterator<Item = (usize, usize)>) -> SignatureReport {
+        let mut offsets = vec![Vec::new(); self.names.len()];
+        found.for_each(|(id, offset)| offsets[id].push(offset));
+
+        SignatureReport {
+            signatures: self
+                .names
+                .iter()
+                .zip(offsets)
+                .map(|(name, mut offsets)| {
+                    offsets.sort_unstable();
+                    SignatureResult {
+                        name: name.clone(),
+                        offsets,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Builds a report from matches in a module based at `base`.
+    #[cfg(any(feature = "internal", feature = "external"))]
+    pub(crate) fn report_matches(
+        &self,
+        base: usize,
+        found: impl Iterator<Item = (usize, crate::Match)>,
+    ) -> SignatureReport {
+        self.report(found.map(|(id, m)| (id, m.address() - base)))
+    }
+}
+
+/// How many times a pattern matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignatureStatus {
+    /// Pattern didn't match.
+    Missing,
+    /// Pattern matched exactly once.
+    Unique,
+    /// Pattern matched more than once.
+    Ambiguous,
+}
+
+/// Result of checking a single pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureResult {
+    /// Name the pattern was added with.
+    pub name: String,
+    /// Offsets of all matches from the start of the module, in ascending order.
+    pub offsets: Vec<usize>,
+}
+
+impl SignatureResult {
+    /// Classifies the result by amount of matches.
+    pub fn status(&self) -> SignatureStatus {
+        match self.offsets.len() {
+            0 => SignatureStatus::Missing,
+            1 => SignatureStatus::Unique,
+            _ => SignatureStatus::Ambiguous,
+        }
+    }
+}
+
+/// Results of a [`SignatureChecker`] run, in the order patterns were added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureReport {
+    /// Result of every pattern.
+    pub signatures: Vec<SignatureResult>,
+}
+
+impl SignatureReport {
+    /// Returns the result of the pattern with the specified name.
+    pub fn get(&self, name: &str) -> Option<&SignatureResult> {
+        self.signatures.iter().find(|s| s.name == name)
+    }
+
+    /// Iterates over patterns that didn't match exactly once.
+    pub fn broken(&self) -> impl Iterator<Item = &SignatureResult> {
+        self.signatures
+            .iter()
+            .filter(|s| s.status() != SignatureStatus::Unique)
+    }
+
+    /// Checks if every pattern matched exactly once.
+    pub fn is_healthy(&self) -> bool {
+        self.broken().next().is_none()
+    }
+}
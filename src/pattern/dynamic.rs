@@ -0,0 +1,190 @@
+extern crate alloc;
+
+use super::{parse_token, ByteMatch, Captures};
+use crate::{Matcher, MfError, Pattern};
+use alloc::{string::String, vec::Vec};
+
+/// Represents a sequence of bytes to match against, built at runtime.
+/// Unlike [`Pattern`], parsing is fallible and reports the position of the malformed input.
+/// ```
+/// # use memflex::{DynPattern, MfError};
+/// let data = b"\x11\x22\x33";
+/// let ida = DynPattern::from_ida_style("11 ? 33").unwrap();
+/// let peid = DynPattern::from_peid_style("11 ?? 33").unwrap();
+/// let code = DynPattern::from_code_style(b"\x11\x00\x33", "x?x").unwrap();
+/// assert!(ida.matches(data) && peid.matches(data) && code.matches(data));
+///
+/// assert!(matches!(DynPattern::from_ida_style("11 2G"), Err(MfError::InvalidPattern(4))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynPattern(pub(crate) Vec<ByteMatch>, pub(crate) Captures);
+
+#[allow(clippy::wrong_self_convention)]
+impl DynPattern {
+    fn from_ida_peid_style(pat: &str, peid: bool) -> crate::Result<Self> {
+        let mut out = Vec::new();
+        let mut captures = Captures::new();
+
+        for (pos, token) in tokens(pat) {
+            if token == "&" {
+                captures = captures
+                    .with(out.len())
+                    .ok_or(MfError::InvalidPattern(pos))?;
+                continue;
+            }
+
+            let m = parse_token(token.as_bytes(), 0, token.len(), peid)
+                .map_err(|i| MfError::InvalidPattern(pos + i))?;
+            out.push(m);
+        }
+
+        Self::non_empty(out, captures)
+    }
+
+    /// Parses pattern from IDA style string.
+    /// Wildcards may be written either as `?` or `??`, nibble wildcards and bitmasks
+    /// and capture slots are supported as in [`Pattern::from_ida_style`].
+    /// ```
+    /// # use memflex::DynPattern;
+    /// let ida = DynPattern::from_ida_style("13 ? D1").unwrap();
+    /// let data = b"\x13\x01\xD1";
+    /// assert!(ida.matches(data));
+    /// ```
+    pub fn from_ida_style(pat: &str) -> crate::Result<Self> {
+        Self::from_ida_peid_style(pat, false)
+    }
+
+    /// Parses pattern from PEID style string.
+    /// ```
+    /// # use memflex::DynPattern;
+    /// let peid = DynPattern::from_peid_style("13 ?? D1").unwrap();
+    /// let data = b"\x13\x01\xD1";
+    /// assert!(peid.matches(data));
+    /// ```
+    pub fn from_peid_style(pat: &str) -> crate::Result<Self> {
+        Self::from_ida_peid_style(pat, true)
+    }
+
+    /// Creates pattern from code style bytes and mask.
+    /// # Errors
+    /// If mask contains anything but `x` or `?`, or is longer than `pat`.
+    /// ```
+    /// # use memflex::DynPattern;
+    /// let pat = DynPattern::from_code_style(b"\x11\x55\xE2", "x?x").unwrap();
+    /// let data = b"\x11\x01\xE2";
+    /// assert!(pat.matches(data));
+    /// ```
+    pub fn from_code_style(pat: &[u8], mask: &str) -> crate::Result<Self> {
+        let out = mask
+            .bytes()
+            .enumerate()
+            .map(|(i, m)| match (m, pat.get(i)) {
+                (b'x', Some(&b)) => Ok(ByteMatch::Exact(b)),
+                (b'?', Some(_)) => Ok(ByteMatch::Any),
+                _ => Err(MfError::InvalidPattern(i)),
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        Self::non_empty(out, Captures::new())
+    }
+
+    /// Parses pattern from code style strings, as produced by [`DynPattern::to_code_style`].
+    /// `pat` consists of `\xHH` escapes and `?` placeholders.
+    /// ```
+    /// # use memflex::DynPattern;
+    /// let pat = DynPattern::from_code_style_str(r"\x11?\xE2", "x?x").unwrap();
+    /// let data = b"\x11\x01\xE2";
+    /// assert!(pat.matches(data));
+    /// ```
+    pub fn from_code_style_str(pat: &str, mask: &str) -> crate::Result<Self> {
+        let bytes = pat.as_bytes();
+        let mut out = Vec::new();
+
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'?' => {
+                    out.push(0);
+                    i += 1;
+                }
+                b'\\' if bytes.get(i + 1) == Some(&b'x') => {
+                    let hi = hex(*bytes.get(i + 2).unwrap_or(&0), i + 2)?;
+                    let lo = hex(*bytes.get(i + 3).unwrap_or(&0), i + 3)?;
+                    out.push(hi << 4 | lo);
+                    i += 4;
+                }
+                _ => return Err(MfError::InvalidPattern(i)),
+            }
+        }
+
+        Self::from_code_style(&out, mask)
+    }
+
+    fn non_empty(out: Vec<ByteMatch>, captures: Captures) -> crate::Result<Self> {
+        if out.is_empty() {
+            Err(MfError::InvalidPattern(0))
+        } else {
+            Ok(Self(out, captures))
+        }
+    }
+
+    /// Converts pattern to IDA style string.
+    pub fn to_ida_style(&self) -> String {
+        super::to_ida_peid_style(&self.0, &self.1, false)
+    }
+
+    /// Converts pattern to PEID style string.
+    pub fn to_peid_style(&self) -> String {
+        super::to_ida_peid_style(&self.0, &self.1, true)
+    }
+
+    /// Converts pattern to code style string, returing pattern and mask.
+    /// # Behavior
+    /// Partially masked bytes can't be expressed in code style and become wildcards,
+    /// capture slots are dropped.
+    pub fn to_code_style(&self) -> (String, String) {
+        super::to_code_style(&self.0)
+    }
+
+    /// Checks if pattern matches byte slice.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() == self.0.len() && self.0.iter().zip(data).all(|(m, b)| m.matches(*b))
+    }
+}
+
+impl<const N: usize> From<Pattern<N>> for DynPattern {
+    fn from(pat: Pattern<N>) -> Self {
+        Self(pat.0.into(), pat.1)
+    }
+}
+
+impl Matcher for DynPattern {
+    fn matches(&self, seq: &[u8]) -> bool {
+        self.matches(seq)
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn exact(&self, idx: usize) -> Option<u8> {
+        self.0.get(idx)?.exact()
+    }
+
+    fn captures(&self) -> &[usize] {
+        self.1.as_slice()
+    }
+}
+
+/// Splits string by whitespace, yielding each token with its byte position.
+fn tokens(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    s.split_ascii_whitespace()
+        .map(move |t| (t.as_ptr() as usize - s.as_ptr() as usize, t))
+}
+
+fn hex(c: u8, pos: usize) -> crate::Result<u8> {
+    (c as char)
+        .to_digit(16)
+        .map(|d| d as u8)
+        .ok_or(MfError::InvalidPattern(pos))
+}
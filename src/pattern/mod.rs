@@ -1,33 +1,143 @@
 mod r#static;
 pub use r#static::*;
+mod scan;
+pub use scan::ScanOptions;
+pub(crate) use scan::*;
+mod capture;
+pub use capture::*;
+#[cfg(feature = "alloc")]
+mod dynamic;
+#[cfg(feature = "alloc")]
+pub use dynamic::*;
+#[cfg(feature = "alloc")]
+mod yara;
+#[cfg(feature = "alloc")]
+pub use yara::*;
+#[cfg(feature = "alloc")]
+mod set;
+#[cfg(feature = "alloc")]
+pub use set::PatternSet;
+#[cfg(feature = "external")]
+pub(crate) use set::{Automaton, SetScan};
+#[cfg(feature = "alloc")]
+mod check;
+#[cfg(feature = "alloc")]
+pub use check::*;
+#[cfg(any(feature = "internal", feature = "external"))]
+mod sigmaker;
+#[cfg(any(feature = "internal", feature = "external"))]
+pub use sigmaker::Signature;
+#[cfg(any(feature = "internal", feature = "external"))]
+pub(crate) use sigmaker::{make_signature, Image};
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) enum ByteMatch {
     Exact(u8),
     Any,
+    /// Value (already masked) and mask
+    Masked(u8, u8),
 }
 
 impl ByteMatch {
+    /// Creates matcher for `value` under `mask`, collapsing full and empty masks.
+    #[inline]
+    pub const fn masked(value: u8, mask: u8) -> Self {
+        match mask {
+            0xFF => ByteMatch::Exact(value),
+            0 => ByteMatch::Any,
+            _ => ByteMatch::Masked(value & mask, mask),
+        }
+    }
+
     #[inline]
     pub const fn matches(self, byte: u8) -> bool {
         match self {
             ByteMatch::Exact(b) => b == byte,
             ByteMatch::Any => true,
+            ByteMatch::Masked(v, m) => byte & m == v,
+        }
+    }
+
+    #[inline]
+    pub const fn exact(self) -> Option<u8> {
+        match self {
+            ByteMatch::Exact(b) => Some(b),
+            _ => None,
         }
     }
 }
 
+#[cfg(feature = "alloc")]
+pub(crate) fn to_ida_peid_style(pat: &[ByteMatch], captures: &Captures, peid: bool) -> String {
+    let mut tokens = alloc::vec::Vec::new();
+
+    for (i, m) in pat.iter().enumerate() {
+        let slots = captures.as_slice().iter().filter(|&&o| o == i);
+        tokens.extend(slots.map(|_| "&".into()));
+
+        tokens.push(match *m {
+            ByteMatch::Exact(b) => alloc::format!("{b:02X}"),
+            ByteMatch::Any => if peid { "??" } else { "?" }.into(),
+            ByteMatch::Masked(v, 0xF0) => alloc::format!("{:X}?", v >> 4),
+            ByteMatch::Masked(v, 0x0F) => alloc::format!("?{v:X}"),
+            ByteMatch::Masked(v, m) => alloc::format!("{v:02X}:{m:02X}"),
+        });
+    }
+
+    let trailing = captures.as_slice().iter().filter(|&&o| o == pat.len());
+    tokens.extend(trailing.map(|_| "&".into()));
+
+    tokens.join(" ")
+}
+
+/// Code style can't express partially masked bytes, those are emitted as wildcards.
+/// Capture slots are dropped.
+#[cfg(feature = "alloc")]
+pub(crate) fn to_code_style(pat: &[ByteMatch]) -> (String, String) {
+    pat.iter()
+        .map(|m| match m {
+            ByteMatch::Exact(b) => (alloc::format!("\\x{b:02X}"), "x"),
+            ByteMatch::Any | ByteMatch::Masked(..) => ("?".into(), "?"),
+        })
+        .unzip::<_, _, String, String>()
+}
+
 /// Trait for generalizing static & dynamic memory patterns.
 #[allow(clippy::len_without_is_empty)]
 pub trait Matcher {
     /// Matches byte sequence agains the pattern
     fn matches(&self, seq: &[u8]) -> bool;
 
-    /// Size of the pattern
+    /// Size of the pattern, for variable length patterns the longest sequence it can match.
     fn len(&self) -> usize;
+
+    /// Shortest sequence the pattern can match, same as [`Matcher::len`] for fixed length patterns.
+    /// Variable length patterns are given windows of [`Matcher::len`] bytes,
+    /// or down to this size at the end of the memory, and match against their prefix.
+    fn min_len(&self) -> usize {
+        self.len()
+    }
+
+    /// Returns the byte that must be at `idx` for the pattern to match, `None` for wildcards.
+    /// Scanner uses it to pick an anchor byte, the default implementation disables that optimization.
+    fn exact(&self, idx: usize) -> Option<u8> {
+        _ = idx;
+        None
+    }
+
+    /// Offsets of the capture slots from the start of the pattern,
+    /// at most [`MAX_CAPTURES`] of them.
+    fn captures(&self) -> &[usize] {
+        &[]
+    }
 }
 
-impl<'a> Matcher for &'a [u8] {
+impl Matcher for &[u8] {
     fn matches(&self, seq: &[u8]) -> bool {
         seq.len() == self.len() && self.iter().zip(seq.iter()).all(|(a, b)| a.eq(b))
     }
@@ -35,4 +145,30 @@ impl<'a> Matcher for &'a [u8] {
     fn len(&self) -> usize {
         (*self as &[u8]).len()
     }
+
+    fn exact(&self, idx: usize) -> Option<u8> {
+        self.get(idx).copied()
+    }
+}
+
+impl<M: Matcher + ?Sized> Matcher for &M {
+    fn matches(&self, seq: &[u8]) -> bool {
+        (*self).matches(seq)
+    }
+
+    fn len(&self) -> usize {
+        (*self).len()
+    }
+
+    fn min_len(&self) -> usize {
+        (*self).min_len()
+    }
+
+    fn exact(&self, idx: usize) -> Option<u8> {
+        (*self).exact(idx)
+    }
+
+    fn captures(&self) -> &[usize] {
+        (*self).captures()
+    }
 }
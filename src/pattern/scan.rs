@@ -0,0 +1,228 @@
+use crate::Matcher;
+use core::ops::Range;
+
+/// Rank of every byte value by how often it occurs in x86-64 binaries,
+/// `0` being the rarest. Used to pick the anchor byte of a pattern.
+#[rustfmt::skip]
+const BYTE_RANK: [u8; 256] = [
+    255, 251, 245, 237, 238, 220, 204, 196, 241, 186, 154, 128, 164, 159, 243, 248,
+    236, 153, 110, 79, 133, 187, 73, 62, 218, 61, 53, 33, 102, 69, 57, 158,
+    229, 124, 40, 101, 250, 82, 48, 47, 199, 131, 26, 50, 95, 49, 170, 44,
+    215, 232, 188, 184, 191, 181, 179, 150, 203, 210, 65, 142, 167, 108, 43, 76,
+    200, 246, 231, 194, 233, 230, 174, 175, 254, 242, 97, 136, 247, 207, 219, 148,
+    197, 68, 176, 208, 190, 134, 169, 118, 163, 55, 119, 117, 135, 106, 92, 240,
+    151, 223, 178, 205, 198, 234, 212, 168, 183, 214, 86, 104, 222, 192, 216, 217,
+    201, 75, 228, 226, 244, 209, 189, 137, 177, 157, 56, 51, 182, 54, 66, 123,
+    193, 146, 90, 235, 227, 224, 121, 78, 147, 252, 80, 249, 162, 239, 116, 125,
+    129, 11, 18, 14, 132, 35, 12, 13, 89, 5, 17, 27, 67, 58, 8, 9,
+    111, 4, 2, 16, 38, 10, 6, 3, 105, 21, 30, 7, 59, 0, 1, 34,
+    140, 23, 19, 15, 120, 20, 103, 74, 152, 109, 114, 37, 172, 42, 113, 77,
+    211, 202, 130, 155, 127, 115, 166, 195, 156, 145, 84, 28, 213, 36, 71, 52,
+    143, 98, 107, 63, 29, 31, 72, 85, 112, 39, 45, 60, 24, 22, 70, 126,
+    171, 100, 87, 25, 64, 32, 96, 99, 225, 221, 83, 160, 88, 94, 91, 122,
+    180, 81, 93, 139, 46, 41, 173, 149, 185, 144, 138, 141, 165, 161, 206, 253,
+];
+
+/// Options of a pattern search, see [`crate::find_pattern_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ScanOptions {
+    /// Searches from the end of the range towards its start, matches are returned in descending order.
+    pub reverse: bool,
+    /// Stops after this amount of matches.
+    pub max_results: Option<usize>,
+    /// Only returns matches at addresses that are a multiple of this value,
+    /// `0` and `1` allow any address.
+    pub align: usize,
+}
+
+impl ScanOptions {
+    /// Alignment to pass to [`Scanner::search`].
+    #[inline]
+    pub(crate) fn stride(&self) -> usize {
+        self.align.max(1)
+    }
+
+    /// Maximum amount of matches to return.
+    #[inline]
+    pub(crate) fn limit(&self) -> usize {
+        self.max_results.unwrap_or(usize::MAX)
+    }
+}
+
+/// Pattern prepared for searching.
+/// # Behavior
+/// Picks the rarest exact byte of the pattern as an anchor, searches for it with SIMD
+/// and only verifies the pattern at the positions where the anchor was found.
+/// Patterns without exact bytes are verified at every position.
+pub(crate) struct Scanner<M> {
+    pat: M,
+    anchor: Option<(usize, u8)>,
+}
+
+impl<M: Matcher> Scanner<M> {
+    pub fn new(pat: M) -> Self {
+        let anchor = (0..pat.min_len())
+            .filter_map(|i| Some((i, pat.exact(i)?)))
+            .min_by_key(|&(_, b)| BYTE_RANK[b as usize]);
+
+        Self { pat, anchor }
+    }
+
+    /// Returns the offset of the first match in `hay` starting at or after `from`.
+    #[cfg(any(feature = "internal", feature = "external"))]
+    #[inline]
+    pub fn find(&self, hay: &[u8], from: usize) -> Option<usize> {
+        self.find_in_chunk(hay, from, true)
+    }
+
+    /// Same as [`Scanner::find`] for a chunk of memory that may continue after `hay`.
+    #[cfg(any(feature = "internal", feature = "external"))]
+    #[inline]
+    pub fn find_in_chunk(&self, hay: &[u8], from: usize, last: bool) -> Option<usize> {
+        self.search(hay, from..usize::MAX, last, false, 0, 1)
+    }
+
+    /// Returns the offset of the match in `range` closest to its start, or to its end if `rev` is set.
+    /// Only offsets where `base + offset` is a multiple of `align` are considered.
+    /// Unless `last` is set, `hay` is a chunk of memory that may continue after it and
+    /// variable length patterns are only matched against full windows,
+    /// positions that don't have one are left for the next chunk.
+    pub fn search(
+        &self,
+        hay: &[u8],
+        range: Range<usize>,
+        last: bool,
+        rev: bool,
+        base: usize,
+        align: usize,
+    ) -> Option<usize> {
+        let len = self.pat.len();
+        let window = if last { self.pat.min_len() } else { len };
+        let end = hay.len().checked_sub(window)? + 1;
+        let (mut lo, mut hi) = (range.start, range.end.min(end));
+
+        while lo < hi {
+            let candidate = match (self.anchor, rev) {
+                (Some((offset, byte)), false) => {
+                    lo + find_byte(&hay[lo + offset..hi + offset], byte)?
+                }
+                (Some((offset, byte)), true) => {
+                    lo + rfind_byte(&hay[lo + offset..hi + offset], byte)?
+                }
+                (None, false) => lo,
+                (None, true) => hi - 1,
+            };
+
+            let misalign = base.wrapping_add(candidate) % align;
+            if misalign != 0 {
+                if rev {
+                    hi = (candidate + 1).checked_sub(misalign)?;
+                } else {
+                    lo = candidate + (align - misalign);
+                }
+                continue;
+            }
+
+            if self
+                .pat
+                .matches(&hay[candidate..(candidate + len).min(hay.len())])
+            {
+                return Some(candidate);
+            }
+
+            if rev {
+                hi = candidate;
+            } else {
+                lo = candidate + 1;
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the index of the first occurrence of `needle` in `hay`.
+pub(crate) fn find_byte(hay: &[u8], needle: u8) -> Option<usize> {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        #[cfg(feature = "std")]
+        if std::is_x86_feature_detected!("avx2") {
+            return find_byte_avx2(hay, needle);
+        }
+
+        find_byte_sse2(hay, needle)
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    hay.iter().position(|&b| b == needle)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn find_byte_sse2(hay: &[u8], needle: u8) -> Option<usize> {
+    use core::arch::x86_64::*;
+
+    let n = _mm_set1_epi8(needle as i8);
+    let mut i = 0;
+    while i + 16 <= hay.len() {
+        let chunk = _mm_loadu_si128(hay.as_ptr().add(i).cast());
+        let mask = _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, n)) as u32;
+        if mask != 0 {
+            return Some(i + mask.trailing_zeros() as usize);
+        }
+        i += 16;
+    }
+
+    hay[i..].iter().position(|&b| b == needle).map(|p| i + p)
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "std"))]
+#[target_feature(enable = "avx2")]
+unsafe fn find_byte_avx2(hay: &[u8], needle: u8) -> Option<usize> {
+    use core::arch::x86_64::*;
+
+    let n = _mm256_set1_epi8(needle as i8);
+    let mut i = 0;
+    while i + 64 <= hay.len() {
+        let a = _mm256_cmpeq_epi8(_mm256_loadu_si256(hay.as_ptr().add(i).cast()), n);
+        let b = _mm256_cmpeq_epi8(_mm256_loadu_si256(hay.as_ptr().add(i + 32).cast()), n);
+        if _mm256_movemask_epi8(_mm256_or_si256(a, b)) != 0 {
+            let mask = _mm256_movemask_epi8(a) as u32 as u64
+                | (_mm256_movemask_epi8(b) as u32 as u64) << 32;
+            return Some(i + mask.trailing_zeros() as usize);
+        }
+        i += 64;
+    }
+
+    find_byte_sse2(&hay[i..], needle).map(|p| i + p)
+}
+
+/// Returns the index of the last occurrence of `needle` in `hay`.
+pub(crate) fn rfind_byte(hay: &[u8], needle: u8) -> Option<usize> {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        rfind_byte_sse2(hay, needle)
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    hay.iter().rposition(|&b| b == needle)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn rfind_byte_sse2(hay: &[u8], needle: u8) -> Option<usize> {
+    use core::arch::x86_64::*;
+
+    let n = _mm_set1_epi8(needle as i8);
+    let mut end = hay.len();
+    while end >= 16 {
+        let chunk = _mm_loadu_si128(hay.as_ptr().add(end - 16).cast());
+        let mask = _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, n)) as u32;
+        if mask != 0 {
+            return Some(end - 16 + (31 - mask.leading_zeros()) as usize);
+        }
+        end -= 16;
+    }
+
+    hay[..end].iter().rposition(|&b| b == needle)
+}
@@ -0,0 +1,242 @@
+extern crate alloc;
+use crate::{Match, Matcher};
+use alloc::{boxed::Box, collections::VecDeque, vec, vec::Vec};
+use core::borrow::Borrow;
+
+/// Longest literal taken from a pattern, keeps the automaton small.
+const MAX_LITERAL: usize = 8;
+const NONE: u32 = u32::MAX;
+
+struct Entry {
+    pat: Box<dyn Matcher + Send + Sync>,
+    /// Offset and length of the exact byte run used to find candidates.
+    literal: (usize, usize),
+}
+
+/// Collection of patterns that are searched for in a single pass over memory.
+/// # Behavior
+/// The longest run of exact bytes of every pattern is put into an Aho–Corasick automaton,
+/// candidates it finds are verified against the full pattern.
+/// Patterns without exact bytes are checked at every position.
+/// ```
+/// # use memflex::{ida_pat, DynPattern, PatternSet};
+/// let mut set = PatternSet::new();
+/// let a = set.add(ida_pat!("11 ? 33"));
+/// let b = set.add(DynPattern::from_ida_style("33 44").unwrap());
+///
+/// let data = b"\x11\x22\x33\x44";
+/// let mut found = set.scan(data).collect::<Vec<_>>();
+/// found.sort();
+/// assert_eq!(found, [(a, 0), (b, 2)]);
+/// ```
+#[derive(Default)]
+pub struct PatternSet {
+    entries: Vec<Entry>,
+}
+
+impl PatternSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a pattern to the set, returning its id.
+    /// Ids are assigned sequentially starting with `0`.
+    pub fn add(&mut self, pat: impl Matcher + Send + Sync + 'static) -> usize {
+        let literal = longest_literal(&pat);
+        self.entries.push(Entry {
+            pat: Box::new(pat),
+            literal,
+        });
+        self.entries.len() - 1
+    }
+
+    /// Returns the pattern with the specified id.
+    pub fn get(&self, id: usize) -> Option<&(dyn Matcher + Send + Sync)> {
+        self.entries.get(id).map(|e| &*e.pat)
+    }
+
+    /// Creates a match of the pattern with the specified id at `address`.
+    pub(crate) fn match_at(&self, id: usize, address: usize) -> Match {
+        Match::new(&*self.entries[id].pat, address)
+    }
+
+    /// Amount of patterns in the set.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Length of the longest pattern in the set.
+    pub fn max_len(&self) -> usize {
+        self.entries.iter().map(|e| e.pat.len()).max().unwrap_or(0)
+    }
+
+    /// Finds all occurences of all patterns in `data`, returning `(id, offset)` pairs.
+    /// # Behavior
+    /// Matches are reported in order of the position their literal ends at,
+    /// which isn't necessarily the order of their offsets.
+    pub fn scan<'a>(&'a self, data: &'a [u8]) -> impl Iterator<Item = (usize, usize)> + 'a {
+        SetScan::new(self, Automaton::new(self), data, true)
+    }
+}
+
+/// Returns offset and length of the longest run of exact bytes in a pattern.
+fn longest_literal(pat: &impl Matcher) -> (usize, usize) {
+    let (mut best, mut run) = ((0, 0), (0, 0));
+    for i in 0..pat.len() {
+        if pat.exact(i).is_some() {
+            run = if run.1 == 0 {
+                (i, 1)
+            } else {
+                (run.0, run.1 + 1)
+            };
+            if run.1 > best.1 {
+                best = run;
+            }
+        } else {
+            run = (0, 0);
+        }
+    }
+
+    (best.0, best.1.min(MAX_LITERAL))
+}
+
+/// Aho–Corasick automaton over the literals of a [`PatternSet`], compiled into a DFA.
+pub(crate) struct Automaton {
+    delta: Vec<[u32; 256]>,
+    /// Ids of patterns whose literal ends in the state.
+    outputs: Vec<Vec<usize>>,
+    /// Ids of patterns without a literal.
+    always: Vec<usize>,
+}
+
+impl Automaton {
+    pub fn new(set: &PatternSet) -> Self {
+        let mut delta = vec![[NONE; 256]];
+        let mut outputs = vec![vec![]];
+        let mut always = vec![];
+
+        for (id, e) in set.entries.iter().enumerate() {
+            let (offset, len) = e.literal;
+            if len == 0 {
+                always.push(id);
+                continue;
+            }
+
+            let mut state = 0;
+            for i in offset..offset + len {
+                let b = e.pat.exact(i).unwrap_or_default() as usize;
+                if delta[state][b] == NONE {
+                    delta.push([NONE; 256]);
+                    outputs.push(vec![]);
+                    delta[state][b] = (delta.len() - 1) as u32;
+                }
+                state = delta[state][b] as usize;
+            }
+            outputs[state].push(id);
+        }
+
+        let mut fail = vec![0; delta.len()];
+        let mut queue = VecDeque::new();
+        for next in delta[0].iter_mut() {
+            match *next {
+                NONE => *next = 0,
+                n => queue.push_back(n as usize),
+            }
+        }
+
+        // Breadth first order guarantees failure states are complete before they're used.
+        while let Some(state) = queue.pop_front() {
+            let f = fail[state];
+            let inherited = outputs[f].clone();
+            outputs[state].extend(inherited);
+
+            let fallback = delta[f];
+            for (next, fallback) in delta[state].iter_mut().zip(fallback) {
+                match *next {
+                    NONE => *next = fallback,
+                    n => {
+                        fail[n as usize] = fallback as usize;
+                        queue.push_back(n as usize);
+                    }
+                }
+            }
+        }
+
+        Self {
+            delta,
+            outputs,
+            always,
+        }
+    }
+}
+
+/// Single pass over a slice, yielding `(id, offset)` of verified matches.
+pub(crate) struct SetScan<'a, A> {
+    set: &'a PatternSet,
+    automaton: A,
+    data: &'a [u8],
+    pos: usize,
+    state: usize,
+    pending: VecDeque<(usize, usize)>,
+    /// Memory doesn't continue after `data`, see [`crate::Scanner::search`].
+    last: bool,
+}
+
+impl<'a, A: Borrow<Automaton>> SetScan<'a, A> {
+    pub fn new(set: &'a PatternSet, automaton: A, data: &'a [u8], last: bool) -> Self {
+        Self {
+            last,
+            set,
+            automaton,
+            data,
+            pos: 0,
+            state: 0,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<A: Borrow<Automaton>> Iterator for SetScan<'_, A> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(found) = self.pending.pop_front() {
+                return Some(found);
+            }
+
+            let pos = self.pos;
+            let byte = *self.data.get(pos)?;
+            self.pos += 1;
+
+            let automaton = self.automaton.borrow();
+            self.state = automaton.delta[self.state][byte as usize] as usize;
+
+            let entries = &self.set.entries;
+            let verify = |id: usize, start: usize| {
+                let pat = &entries[id].pat;
+                let window = if self.last { pat.min_len() } else { pat.len() };
+                let rest = self.data.get(start..)?;
+                let seq = &rest[..rest.len().min(pat.len())];
+                (seq.len() >= window && pat.matches(seq)).then_some((id, start))
+            };
+
+            let always = automaton.always.iter().map(|&id| (id, Some(pos)));
+            let literals = automaton.outputs[self.state].iter().map(|&id| {
+                let (offset, len) = entries[id].literal;
+                (id, (pos + 1).checked_sub(offset + len))
+            });
+            self.pending.extend(
+                always
+                    .chain(literals)
+                    .filter_map(|(id, start)| verify(id, start?)),
+            );
+        }
+    }
+}
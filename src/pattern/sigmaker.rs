@@ -0,0 +1,144 @@
+extern crate alloc;
+use super::{ByteMatch, Captures, DynPattern, Scanner};
+use crate::x86::{decode, Branch};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Amount of matches that are remembered and rechecked instead of rescanning the module.
+const MAX_CANDIDATES: usize = 0x400;
+
+/// Signature generated for an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// Pattern starting at the requested address.
+    pub pattern: DynPattern,
+    /// Pattern matches only once in the module.
+    /// If `false` the pattern reached its maximum length before becoming unique.
+    pub unique: bool,
+}
+
+/// Readable memory of a module, as a list of `(address, bytes)` segments.
+pub(crate) struct Image<'a> {
+    pub segments: Vec<(usize, &'a [u8])>,
+    /// Address range of the whole module, used to detect absolute addresses.
+    pub range: Range<usize>,
+}
+
+impl Image<'_> {
+    fn code_at(&self, address: usize) -> Option<&[u8]> {
+        self.segments.iter().find_map(|&(start, data)| {
+            address
+                .checked_sub(start)
+                .and_then(|offset| data.get(offset..))
+                .filter(|code| !code.is_empty())
+        })
+    }
+
+    /// Addresses of all matches of `pat`, or `None` if there are more than [`MAX_CANDIDATES`].
+    fn find_all(&self, pat: &DynPattern) -> Option<Vec<usize>> {
+        let scanner = Scanner::new(pat);
+        let mut found = Vec::new();
+        for &(start, data) in &self.segments {
+            let mut from = 0;
+            while let Some(offset) = scanner.find(data, from) {
+                if found.len() == MAX_CANDIDATES {
+                    return None;
+                }
+                found.push(start + offset);
+                from = offset + 1;
+            }
+        }
+        Some(found)
+    }
+
+    /// Checks if a 4 or 8 byte operand holds an address inside the module.
+    fn is_address(&self, value: &[u8]) -> bool {
+        let value = match *value {
+            [a, b, c, d] => u32::from_le_bytes([a, b, c, d]) as usize,
+            [a, b, c, d, e, f, g, h] => u64::from_le_bytes([a, b, c, d, e, f, g, h]) as usize,
+            _ => return false,
+        };
+        self.range.contains(&value)
+    }
+
+    fn matches_at(&self, pat: &DynPattern, address: usize) -> bool {
+        self.code_at(address)
+            .and_then(|code| code.get(..pat.0.len()))
+            .is_some_and(|window| pat.matches(window))
+    }
+}
+
+/// Creates a signature for `address`, growing it one instruction at a time until it is unique.
+/// # Behavior
+/// Displacements of relative branches and RIP-relative operands, as well as
+/// immediates and displacements that point into the module are replaced with wildcards.
+/// Bytes that can't be decoded as an instruction are taken as is.
+pub(crate) fn make_signature(image: &Image, address: usize, max_len: usize) -> Option<Signature> {
+    let code = image.code_at(address)?;
+    let code = &code[..code.len().min(max_len)];
+
+    let mut bytes = Vec::new();
+    let mut candidates = None::<Vec<usize>>;
+    let mut pos = 0;
+
+    while pos < code.len() {
+        let insn = decode(&code[pos..]).filter(|i| pos + i.len <= code.len());
+        let len = insn.map_or(1, |i| i.len);
+        let start = bytes.len();
+        bytes.extend(code[pos..pos + len].iter().map(|&b| ByteMatch::Exact(b)));
+
+        if let Some(insn) = insn {
+            let rel = insn.branch.is_some_and(|b| b != Branch::Loop);
+            let operands = [(insn.disp, insn.rip_relative), (insn.imm, rel)];
+
+            for (operand, relative) in operands {
+                let Some((offset, size)) = operand else {
+                    continue;
+                };
+
+                let value = &code[pos + offset..pos + offset + size];
+                if (relative && size == 4) || image.is_address(value) {
+                    bytes[start + offset..start + offset + size].fill(ByteMatch::Any);
+                }
+            }
+        }
+        pos += len;
+
+        let pattern = trimmed(&bytes);
+        if pattern.0.is_empty() {
+            continue;
+        }
+
+        let count = match &mut candidates {
+            Some(c) => {
+                c.retain(|&a| image.matches_at(&pattern, a));
+                c.len()
+            }
+            None => {
+                candidates = image.find_all(&pattern);
+                candidates.as_ref().map_or(usize::MAX, Vec::len)
+            }
+        };
+
+        if count <= 1 {
+            return Some(Signature {
+                pattern,
+                unique: count == 1,
+            });
+        }
+    }
+
+    Some(Signature {
+        pattern: trimmed(&bytes),
+        unique: false,
+    })
+}
+
+/// Pattern without trailing wildcards.
+fn trimmed(bytes: &[ByteMatch]) -> DynPattern {
+    let len = bytes
+        .iter()
+        .rposition(|b| *b != ByteMatch::Any)
+        .map_or(0, |i| i + 1);
+    DynPattern(bytes[..len].to_vec(), Captures::new())
+}
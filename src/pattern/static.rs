@@ -3,7 +3,7 @@ extern crate alloc;
 #[cfg(feature = "alloc")]
 use alloc::string::String;
 
-use super::ByteMatch;
+use super::{ByteMatch, Captures};
 use crate::Matcher;
 
 #[test]
@@ -23,6 +23,75 @@ const fn single(c: char) -> u8 {
     }
 }
 
+/// Parses half of a byte token, returning its value and mask.
+const fn nibble(c: u8, pos: usize) -> Result<(u8, u8), usize> {
+    match c {
+        b'?' => Ok((0, 0)),
+        b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F' => Ok((single(c as char), 0xF)),
+        _ => Err(pos),
+    }
+}
+
+/// Parses two hex digits at `pos`, wildcard nibbles included.
+pub(super) const fn hex_pair(src: &[u8], pos: usize) -> Result<(u8, u8), usize> {
+    let (hv, hm) = match nibble(src[pos], pos) {
+        Ok(v) => v,
+        Err(e) => return Err(e),
+    };
+    let (lv, lm) = match nibble(src[pos + 1], pos + 1) {
+        Ok(v) => v,
+        Err(e) => return Err(e),
+    };
+
+    Ok((hv << 4 | lv, hm << 4 | lm))
+}
+
+/// Parses a single IDA/PEID byte token `src[start..end]`.
+/// # Syntax
+/// * `48` - exact byte
+/// * `?` (IDA only) or `??` - any byte
+/// * `4?`, `?8` - nibble wildcards
+/// * `40:F8` - byte masked with an arbitrary bitmask
+///
+/// On failure returns the position of the offending character.
+pub(super) const fn parse_token(
+    src: &[u8],
+    start: usize,
+    end: usize,
+    peid: bool,
+) -> Result<ByteMatch, usize> {
+    match end - start {
+        1 if src[start] == b'?' && !peid => Ok(ByteMatch::Any),
+        2 => match hex_pair(src, start) {
+            Ok((value, mask)) => Ok(ByteMatch::masked(value, mask)),
+            Err(e) => Err(e),
+        },
+        5 if src[start + 2] == b':' => match (hex_pair(src, start), hex_pair(src, start + 3)) {
+            (Ok((value, 0xFF)), Ok((mask, 0xFF))) => Ok(ByteMatch::masked(value, mask)),
+            (Err(e), _) | (_, Err(e)) => Err(e),
+            (Ok(_), Ok(_)) => Err(start),
+        },
+        _ => Err(start),
+    }
+}
+
+/// Returns `(start, end)` of the next whitespace separated token at or after `i`.
+pub(super) const fn next_token(src: &[u8], mut i: usize) -> Option<(usize, usize)> {
+    while i < src.len() && src[i].is_ascii_whitespace() {
+        i += 1;
+    }
+
+    if i == src.len() {
+        return None;
+    }
+
+    let start = i;
+    while i < src.len() && !src[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    Some((start, i))
+}
+
 /// Represents a staticly built sequence of bytes to match against.
 /// ```
 /// # use memflex::{ida_pat, peid_pat, code_pat};
@@ -31,46 +100,35 @@ const fn single(c: char) -> u8 {
 /// let peid = peid_pat!("11 ?? 33");
 /// let code = code_pat!(b"\x11\x00\x33", "x?x");
 /// assert!(ida.matches(data) && peid.matches(data) && code.matches(data));
+///
+/// // Nibble wildcards and bitmasks
+/// let masked = ida_pat!("1? ?2 30:F0");
+/// assert!(masked.matches(data));
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Pattern<const N: usize>(pub(crate) [ByteMatch; N]);
+pub struct Pattern<const N: usize>(pub(crate) [ByteMatch; N], pub(crate) Captures);
 
 #[allow(clippy::wrong_self_convention)]
 impl<const N: usize> Pattern<N> {
-    #[cfg(feature = "alloc")]
-    fn to_ida_peid_style(&self, peid: bool) -> String {
-        self.0
-            .iter()
-            .map(|m| match m {
-                ByteMatch::Exact(b) => alloc::format!("{b:02X}"),
-                ByteMatch::Any => if peid { "??" } else { "?" }.into(),
-            })
-            .collect::<alloc::vec::Vec<_>>()
-            .join(" ")
-    }
-
     /// Converts pattern to IDA style string.
     #[cfg(feature = "alloc")]
     pub fn to_ida_style(&self) -> String {
-        self.to_ida_peid_style(false)
+        super::to_ida_peid_style(&self.0, &self.1, false)
     }
 
     /// Converts pattern to PEID style string.
     #[cfg(feature = "alloc")]
     pub fn to_peid_style(&self) -> String {
-        self.to_ida_peid_style(true)
+        super::to_ida_peid_style(&self.0, &self.1, true)
     }
 
     /// Converts pattern to code style string, returing pattern and mask.
+    /// # Behavior
+    /// Partially masked bytes can't be expressed in code style and become wildcards,
+    /// capture slots are dropped.
     #[cfg(feature = "alloc")]
     pub fn to_code_style(&self) -> (String, String) {
-        self.0
-            .iter()
-            .map(|m| match m {
-                ByteMatch::Exact(b) => (alloc::format!("\\x{b:02X}"), "x"),
-                ByteMatch::Any => ("?".into(), "?"),
-            })
-            .unzip::<_, _, String, String>()
+        super::to_code_style(&self.0)
     }
 
     /// Checks if pattern matches byte slice.
@@ -98,34 +156,36 @@ impl<const N: usize> Pattern<N> {
 
     const fn from_ida_peid_style(pat: &'static str, peid: bool) -> Pattern<N> {
         let mut out = [ByteMatch::Any; N];
+        let mut captures = Captures::new();
+        let src = pat.as_bytes();
 
         let mut i = 0;
         let mut j = 0;
-
-        while i < pat.len() - 1 {
-            let c1 = pat.as_bytes()[i];
-            let c2 = pat.as_bytes()[i + 1];
-
-            if c1 == b'?' && c2 == if peid { b'?' } else { b' ' } {
-                out[j] = ByteMatch::Any;
-                j += 1;
-                i += 2 + (peid as usize);
-                continue;
-            } else if c2 == b' ' {
-                i += 2;
+        while let Some((start, end)) = next_token(src, i) {
+            i = end;
+
+            if end - start == 1 && src[start] == b'&' {
+                captures = match captures.with(j) {
+                    Some(c) => c,
+                    None => panic!("Too many capture slots"),
+                };
                 continue;
             }
 
-            let byte = single(c1 as char) * 0x10 + single(c2 as char);
-            out[j] = ByteMatch::Exact(byte);
+            out[j] = match parse_token(src, start, end, peid) {
+                Ok(m) => m,
+                Err(_) => panic!("Invalid pattern"),
+            };
             j += 1;
-            i += 1;
         }
 
-        Self(out)
+        Self(out, captures)
     }
 
     /// Creates pattern from IDA style string.
+    /// Besides `?` wildcards, bytes can have wildcard nibbles (`4?`, `?5`)
+    /// or an arbitrary bitmask after a colon (`40:F8`).
+    /// `&` marks a capture slot at the position of the next byte, see [`crate::Match`].
     /// ```
     /// # use memflex::{ida_pat, peid_pat} ;
     /// // Pattern parsing is a contant call and happens at compile time.
@@ -138,6 +198,7 @@ impl<const N: usize> Pattern<N> {
     }
 
     /// Creates pattern from PEID style string.
+    /// Supports the same nibble wildcards, bitmasks and capture slots as [`Pattern::from_ida_style`].
     /// ```
     /// # use memflex::{ida_pat, peid_pat} ;
     /// // Pattern parsing is a contant call and happens at compile time.
@@ -181,7 +242,7 @@ impl<const N: usize> Pattern<N> {
             i += 1;
         }
 
-        Self(out)
+        Self(out, Captures::new())
     }
 }
 
@@ -193,6 +254,14 @@ impl<const N: usize> Matcher for Pattern<N> {
     fn len(&self) -> usize {
         N
     }
+
+    fn exact(&self, idx: usize) -> Option<u8> {
+        self.0.get(idx)?.exact()
+    }
+
+    fn captures(&self) -> &[usize] {
+        self.1.as_slice()
+    }
 }
 
 /// Generates a pattern from IDA style string.
@@ -226,24 +295,15 @@ macro_rules! peid_pat {
 }
 
 #[doc(hidden)]
-pub const fn __ida_peid_count(pat: &'static str, peid: bool) -> usize {
+pub const fn __ida_peid_count(pat: &'static str, _peid: bool) -> usize {
     let mut i = 0;
     let mut j = 0;
 
-    while i < pat.len() - 1 {
-        let c1 = pat.as_bytes()[i];
-        let c2 = pat.as_bytes()[i + 1];
-
-        if c1 == b'?' && c2 == if peid { b'?' } else { b' ' } {
+    while let Some((start, end)) = next_token(pat.as_bytes(), i) {
+        if end - start != 1 || pat.as_bytes()[start] != b'&' {
             j += 1;
-            i += 2 + (peid as usize);
-        } else if c2 == b' ' {
-            i += 2;
-            continue;
         }
-
-        j += 1;
-        i += 1;
+        i = end;
     }
 
     j
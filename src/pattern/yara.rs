@@ -0,0 +1,347 @@
+extern crate alloc;
+
+use super::{hex_pair, ByteMatch};
+use crate::{Matcher, MfError};
+use alloc::{format, string::String, vec, vec::Vec};
+
+/// Longest jump allowed, keeps windows of the pattern reasonably small.
+const MAX_JUMP: usize = 0x10000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Node {
+    Byte(ByteMatch),
+    /// Skips from `.0` to `.1` bytes (inclusive).
+    Jump(usize, usize),
+    Alt(Vec<Vec<Node>>),
+}
+
+/// Hex string in YARA syntax, supports jumps and alternatives on top of
+/// `??` wildcards and nibble wildcards.
+/// # Behavior
+/// Matches have variable length, [`Matcher::len`] is the length of the longest one.
+/// ```
+/// # use memflex::{YaraPattern, Matcher, MfError};
+/// let pat = YaraPattern::from_yara_style("{ ( 48 | 49 ) 8B [1-2] C3 }").unwrap();
+/// assert!(pat.matches(b"\x48\x8B\x00\xC3"));
+/// assert!(pat.matches(b"\x49\x8B\x00\x00\xC3"));
+/// assert!(!pat.matches(b"\x4A\x8B\x00\xC3"));
+/// assert_eq!((pat.min_len(), pat.len()), (4, 5));
+///
+/// assert!(matches!(YaraPattern::from_yara_style("4D [2-] 5A"), Err(MfError::InvalidPattern(6))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct YaraPattern {
+    nodes: Vec<Node>,
+    /// Bytes at fixed positions from the start of the pattern.
+    prefix: Vec<ByteMatch>,
+    min_len: usize,
+    max_len: usize,
+}
+
+#[allow(clippy::wrong_self_convention)]
+impl YaraPattern {
+    /// Parses pattern from YARA hex string, surrounding braces are optional.
+    /// # Syntax
+    /// * `4D`, `??`, `4?`, `?D` - bytes, wildcards and nibble wildcards
+    /// * `[4]`, `[2-6]` - jumps over a fixed or bounded amount of bytes,
+    ///   unbounded jumps aren't supported
+    /// * `( 48 | 49 8B )` - alternatives, can be nested and contain jumps
+    ///
+    /// Pattern can't start or end with a jump.
+    pub fn from_yara_style(pat: &str) -> crate::Result<Self> {
+        let mut parser = Parser {
+            src: pat.as_bytes(),
+            pos: 0,
+            last_number: 0,
+        };
+
+        let braced = parser.peek() == Some(b'{');
+        if braced {
+            parser.pos += 1;
+        }
+
+        let start = parser.peek().map(|_| parser.pos);
+        let nodes = parser.sequence()?;
+        if matches!(nodes.first(), Some(Node::Jump(..))) {
+            return Err(MfError::InvalidPattern(start.unwrap_or_default()));
+        }
+        if matches!(nodes.last(), Some(Node::Jump(..))) {
+            return Err(MfError::InvalidPattern(parser.last_jump()));
+        }
+
+        if braced {
+            parser.expect(b'}')?;
+        }
+        if parser.peek().is_some() {
+            return parser.err();
+        }
+
+        let (min_len, max_len) = lengths(&nodes);
+        let mut prefix = Vec::new();
+        fixed_prefix(&nodes, &mut prefix);
+
+        Ok(Self {
+            nodes,
+            prefix,
+            min_len,
+            max_len,
+        })
+    }
+
+    /// Converts pattern to YARA hex string.
+    /// Returns `None` if some byte is masked with anything but a nibble mask,
+    /// YARA has no syntax for those.
+    pub fn to_yara_style(&self) -> Option<String> {
+        Some(format!("{{ {} }}", to_yara(&self.nodes)?))
+    }
+
+    /// Checks if `data` starts with a match of the pattern.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() >= self.min_len
+            && self.prefix.iter().zip(data).all(|(b, &v)| b.matches(v))
+            && !advance(&self.nodes, data, vec![0]).is_empty()
+    }
+}
+
+impl Matcher for YaraPattern {
+    fn matches(&self, seq: &[u8]) -> bool {
+        self.matches(seq)
+    }
+
+    fn len(&self) -> usize {
+        self.max_len
+    }
+
+    fn min_len(&self) -> usize {
+        self.min_len
+    }
+
+    fn exact(&self, idx: usize) -> Option<u8> {
+        self.prefix.get(idx)?.exact()
+    }
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    /// Position of the last number parsed, for error reporting.
+    last_number: usize,
+}
+
+impl Parser<'_> {
+    /// Returns the next non whitespace character without consuming it.
+    fn peek(&mut self) -> Option<u8> {
+        while self.src.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+        self.src.get(self.pos).copied()
+    }
+
+    fn err<T>(&self) -> crate::Result<T> {
+        Err(MfError::InvalidPattern(self.pos))
+    }
+
+    fn expect(&mut self, c: u8) -> crate::Result<()> {
+        if self.peek() != Some(c) {
+            return self.err();
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Parses nodes up to the end of input, `|`, `)` or `}`.
+    fn sequence(&mut self) -> crate::Result<Vec<Node>> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(b'|' | b')' | b'}') => break,
+                Some(b'(') => {
+                    self.pos += 1;
+                    nodes.push(self.alternatives()?);
+                }
+                Some(b'[') => {
+                    self.pos += 1;
+                    nodes.push(self.jump()?);
+                }
+                Some(_) if self.pos + 1 < self.src.len() => {
+                    let (value, mask) =
+                        hex_pair(self.src, self.pos).map_err(MfError::InvalidPattern)?;
+                    nodes.push(Node::Byte(ByteMatch::masked(value, mask)));
+                    self.pos += 2;
+                }
+                Some(_) => return self.err(),
+            }
+        }
+
+        if nodes.is_empty() {
+            return self.err();
+        }
+        Ok(nodes)
+    }
+
+    fn alternatives(&mut self) -> crate::Result<Node> {
+        let mut alts = vec![self.sequence()?];
+        loop {
+            match self.peek() {
+                Some(b'|') => self.pos += 1,
+                Some(b')') => {
+                    self.pos += 1;
+                    return Ok(Node::Alt(alts));
+                }
+                _ => return self.err(),
+            }
+            alts.push(self.sequence()?);
+        }
+    }
+
+    fn jump(&mut self) -> crate::Result<Node> {
+        let min = self.number()?;
+        let max = if self.peek() == Some(b'-') {
+            self.pos += 1;
+            self.number()?
+        } else {
+            min
+        };
+
+        if max < min || max > MAX_JUMP {
+            return Err(MfError::InvalidPattern(self.last_number));
+        }
+        self.expect(b']')?;
+        Ok(Node::Jump(min, max))
+    }
+
+    fn number(&mut self) -> crate::Result<usize> {
+        self.peek();
+        let start = self.pos;
+        self.last_number = start;
+        while self.src.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+
+        core::str::from_utf8(&self.src[start..self.pos])
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .ok_or(MfError::InvalidPattern(start))
+    }
+
+    /// Position of the `[` of the last jump parsed.
+    fn last_jump(&self) -> usize {
+        self.src[..self.pos]
+            .iter()
+            .rposition(|&c| c == b'[')
+            .unwrap_or_default()
+    }
+}
+
+/// Shortest and longest length of a sequence.
+fn lengths(nodes: &[Node]) -> (usize, usize) {
+    nodes.iter().fold((0, 0), |(lo, hi), node| {
+        let (a, b) = match node {
+            Node::Byte(_) => (1, 1),
+            Node::Jump(a, b) => (*a, *b),
+            Node::Alt(alts) => alts
+                .iter()
+                .map(|alt| lengths(alt))
+                .fold((usize::MAX, 0), |(lo, hi), (a, b)| (lo.min(a), hi.max(b))),
+        };
+        (lo + a, hi + b)
+    })
+}
+
+/// Collects bytes at fixed positions up to the first node of variable length,
+/// returns `false` if it stopped early.
+fn fixed_prefix(nodes: &[Node], out: &mut Vec<ByteMatch>) -> bool {
+    for node in nodes {
+        match node {
+            Node::Byte(b) => out.push(*b),
+            Node::Jump(a, b) if a == b => out.extend((0..*a).map(|_| ByteMatch::Any)),
+            Node::Alt(alts) => {
+                let mut prefixes = Vec::new();
+                for alt in alts {
+                    let mut prefix = Vec::new();
+                    if !fixed_prefix(alt, &mut prefix) {
+                        return false;
+                    }
+                    prefixes.push(prefix);
+                }
+
+                let len = prefixes[0].len();
+                if prefixes.iter().any(|p| p.len() != len) {
+                    return false;
+                }
+
+                out.extend((0..len).map(|i| {
+                    let b = prefixes[0][i];
+                    if prefixes.iter().all(|p| p[i] == b) {
+                        b
+                    } else {
+                        ByteMatch::Any
+                    }
+                }));
+            }
+            Node::Jump(..) => return false,
+        }
+    }
+    true
+}
+
+/// Matches `nodes` starting at each of the sorted `positions`,
+/// returns sorted positions right after the matches.
+/// Positions are advanced node by node, so jumps don't multiply the work of later nodes.
+fn advance(nodes: &[Node], data: &[u8], mut positions: Vec<usize>) -> Vec<usize> {
+    for node in nodes {
+        if positions.is_empty() {
+            break;
+        }
+
+        match node {
+            Node::Byte(b) => {
+                positions.retain(|&p| data.get(p).is_some_and(|&v| b.matches(v)));
+                positions.iter_mut().for_each(|p| *p += 1);
+            }
+            Node::Jump(min, max) => {
+                let mut out: Vec<usize> = Vec::new();
+                for p in positions {
+                    let from = (p + min).max(out.last().map_or(0, |&l| l + 1));
+                    out.extend(from..=(p + max).min(data.len()));
+                }
+                positions = out;
+            }
+            Node::Alt(alts) => {
+                let mut out = alts
+                    .iter()
+                    .flat_map(|alt| advance(alt, data, positions.clone()))
+                    .collect::<Vec<_>>();
+                out.sort_unstable();
+                out.dedup();
+                positions = out;
+            }
+        }
+    }
+    positions
+}
+
+fn to_yara(nodes: &[Node]) -> Option<String> {
+    nodes
+        .iter()
+        .map(|node| {
+            Some(match node {
+                Node::Byte(ByteMatch::Exact(b)) => format!("{b:02X}"),
+                Node::Byte(ByteMatch::Masked(v, 0xF0)) => format!("{:X}?", v >> 4),
+                Node::Byte(ByteMatch::Masked(v, 0x0F)) => format!("?{:X}", v & 0xF),
+                Node::Byte(ByteMatch::Any) => "??".into(),
+                Node::Byte(ByteMatch::Masked(..)) => return None,
+                Node::Jump(a, b) if a == b => format!("[{a}]"),
+                Node::Jump(a, b) => format!("[{a}-{b}]"),
+                Node::Alt(alts) => format!(
+                    "( {} )",
+                    alts.iter()
+                        .map(|a| to_yara(a))
+                        .collect::<Option<Vec<_>>>()?
+                        .join(" | ")
+                ),
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|nodes| nodes.join(" "))
+}
@@ -0,0 +1,407 @@
+extern crate alloc;
+use crate::{
+    types::{MemoryRegion, ModuleInfoWithName},
+    MemorySource, MfError, RegionChunks,
+};
+use alloc::{string::String, vec, vec::Vec};
+use core::{
+    fmt::{self, Display},
+    mem::size_of,
+    ops::Range,
+    str::FromStr,
+};
+
+/// Pointer found by [`MemorySource::find_pointers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerHit {
+    /// Address the pointer is stored at.
+    pub address: usize,
+    /// Value of the pointer.
+    pub value: usize,
+    /// Region the pointer is stored in.
+    pub region: MemoryRegion,
+}
+
+impl PointerHit {
+    /// Offset of the pointer from the start of its region.
+    #[inline]
+    pub fn region_offset(&self) -> usize {
+        self.address - self.region.from
+    }
+}
+
+/// Chain of pointers written as an address expression, e.g. `[[libgame.so+0x1A2B0]+0x18]+0x30`,
+/// also found by [`MemorySource::find_pointer_paths`].
+/// # Behavior
+/// Same as `resolve_multilevel(module_base, [offset, offsets..])`.
+/// The base is either a module name with an optional offset, or an absolute address.
+/// The innermost brackets are read first, each offset after `]` is added to the value
+/// that was read. Numbers are either decimal or hexadecimal with the `0x` prefix,
+/// offsets can be negative.
+/// ```
+/// # use memflex::PointerChain;
+/// let chain: PointerChain = "[[libgame.so + 0x1A2B0] + 0x18] - 0x30".parse().unwrap();
+/// assert_eq!(chain.module.as_deref(), Some("libgame.so"));
+/// assert_eq!(chain.offset, 0x1A2B0);
+/// assert_eq!(chain.offsets, [0x18, 0x30_usize.wrapping_neg()]);
+/// assert_eq!(chain.to_string(), "[[libgame.so+0x1A2B0]+0x18]-0x30");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PointerChain {
+    /// Name of the module the chain starts in, `None` if `offset` is an absolute address.
+    pub module: Option<String>,
+    /// Offset from the base of the module, or the address the chain starts at.
+    pub offset: usize,
+    /// Offsets added to the value of each pointer, wrapping around.
+    pub offsets: Vec<usize>,
+}
+
+impl PointerChain {
+    /// Follows the chain, returning the address it leads to.
+    /// # Errors
+    /// * [`MfError::ModuleNotFound`] if the module isn't loaded.
+    /// * [`MfError::InvalidPointer`] with the level and address of the pointer that couldn't be read.
+    pub fn resolve(&self, source: &impl MemorySource) -> crate::Result<usize> {
+        let base = match &self.module {
+            Some(name) => source.find_module(name)?.base as usize,
+            None => 0,
+        };
+
+        let mut address = base.wrapping_add(self.offset);
+        for (level, &offset) in self.offsets.iter().enumerate() {
+            address = source
+                .read_pointer(address)
+                .map_err(|_| MfError::InvalidPointer { level, address })?
+                .wrapping_add(offset);
+        }
+
+        Ok(address)
+    }
+
+    /// Checks if the chain still leads to `target`, e.g. after the process was restarted.
+    pub fn points_to(&self, source: &impl MemorySource, target: usize) -> bool {
+        self.resolve(source).is_ok_and(|a| a == target)
+    }
+}
+
+/// Formats `offset` as a signed hexadecimal number.
+fn write_offset(f: &mut fmt::Formatter<'_>, offset: usize) -> fmt::Result {
+    if (offset as isize) < 0 {
+        write!(f, "-{:#X}", offset.wrapping_neg())
+    } else {
+        write!(f, "+{offset:#X}")
+    }
+}
+
+impl Display for PointerChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", "[".repeat(self.offsets.len()))?;
+        match &self.module {
+            Some(module) => {
+                write!(f, "{module}")?;
+                write_offset(f, self.offset)?;
+            }
+            None => write!(f, "{:#X}", self.offset)?,
+        }
+
+        self.offsets.iter().try_for_each(|&offset| {
+            write!(f, "]")?;
+            write_offset(f, offset)
+        })
+    }
+}
+
+/// Parses an unsigned decimal or `0x` prefixed hexadecimal number.
+fn parse_number(s: &str) -> Option<usize> {
+    let s = s.trim();
+    if !s.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) if hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            usize::from_str_radix(hex, 16).ok()
+        }
+        Some(_) => None,
+        None => s.parse().ok(),
+    }
+}
+
+/// Parses a number prefixed with `+` or `-`.
+fn parse_offset(s: &str) -> Option<usize> {
+    let s = s.trim();
+    if let Some(n) = s.strip_prefix('+') {
+        parse_number(n)
+    } else {
+        parse_number(s.strip_prefix('-')?).map(usize::wrapping_neg)
+    }
+}
+
+impl FromStr for PointerChain {
+    type Err = MfError;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let body = s.trim_start_matches(|c: char| c == '[' || c.is_whitespace());
+        let depth = s[..s.len() - body.len()].matches('[').count();
+
+        // Positions of the preceding `]` and of the first non whitespace character of each part.
+        let mut pos = s.len() - body.len();
+        let mut parts = body.split(']').map(|part| {
+            let (bracket, start) = (
+                pos.wrapping_sub(1),
+                pos + part.len() - part.trim_start().len(),
+            );
+            pos += part.len() + 1;
+            (bracket, start, part.trim())
+        });
+
+        let (_, start, base) = parts.next().unwrap();
+        let (module, offset) = if let Some(address) = parse_number(base) {
+            (None, address)
+        } else {
+            // Module names can contain `+` and `-` too, e.g. `ld-linux-x86-64.so.2`
+            let (name, offset) = base
+                .rmatch_indices(['+', '-'])
+                .find_map(|(i, _)| Some((base[..i].trim(), parse_offset(&base[i..])?)))
+                .unwrap_or((base, 0));
+
+            match parse_number(name) {
+                Some(address) => (None, address.wrapping_add(offset)),
+                None if name.is_empty() || name.contains('[') => {
+                    return Err(MfError::InvalidExpression(start))
+                }
+                None => (Some(name.into()), offset),
+            }
+        };
+
+        let mut offsets = Vec::with_capacity(depth);
+        for (bracket, start, part) in parts {
+            if offsets.len() == depth {
+                return Err(MfError::InvalidExpression(bracket));
+            }
+
+            offsets.push(match part {
+                "" => 0,
+                part => parse_offset(part).ok_or(MfError::InvalidExpression(start))?,
+            });
+        }
+
+        if offsets.len() != depth {
+            return Err(MfError::InvalidExpression(s.len()));
+        }
+
+        Ok(Self {
+            module,
+            offset,
+            offsets,
+        })
+    }
+}
+
+/// Breadth first search over the pointer map, yields chains of each length in turn.
+pub(crate) struct PathSearch {
+    /// `(value, address)` of every pointer, sorted by value.
+    map: Vec<(usize, usize)>,
+    /// `(start, end, index)` of module memory, sorted by start.
+    statics: Vec<(usize, usize, usize)>,
+    modules: Vec<ModuleInfoWithName>,
+    max_depth: usize,
+    max_offset: usize,
+    /// Addresses reached from the target, the first node is the target itself.
+    nodes: Vec<Node>,
+    /// Nodes of the previous level that are left to expand.
+    level: Range<usize>,
+    /// Node being expanded.
+    current: usize,
+    /// Pointers left to try for the current node, as indices into the map.
+    candidates: Range<usize>,
+    /// Length of the chains searched for.
+    depth: usize,
+}
+
+struct Node {
+    /// Address a pointer has to lead to.
+    address: usize,
+    /// Offset from the pointer at `address` to the address of the parent.
+    offset: usize,
+    parent: usize,
+}
+
+impl PathSearch {
+    pub fn new(
+        source: &impl MemorySource,
+        target: usize,
+        max_depth: usize,
+        max_offset: usize,
+    ) -> crate::Result<Self> {
+        let mut regions = source.maps()?;
+        regions.retain(|r| r.prot.read());
+        regions.sort_unstable_by_key(|r| r.from);
+
+        let valid = |value: usize| {
+            let i = regions.partition_point(|r| r.to <= value);
+            regions.get(i).is_some_and(|r| r.from <= value)
+        };
+
+        let mut map = Vec::new();
+        let mut chunks = RegionChunks::new(source, regions.clone(), 0);
+        while chunks.advance() {
+            let (data, base) = (chunks.data(), chunks.base());
+            let skip = base.wrapping_neg() % size_of::<usize>();
+
+            for (i, value) in data[skip.min(data.len())..]
+                .chunks_exact(size_of::<usize>())
+                .enumerate()
+            {
+                let value = usize::from_ne_bytes(value.try_into().unwrap());
+                if valid(value) {
+                    map.push((value, base + skip + i * size_of::<usize>()));
+                }
+            }
+        }
+        map.sort_unstable();
+
+        let modules = source.modules()?;
+        let mut statics = modules
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let end = m.base as usize + m.size;
+                (m.base as usize, static_end(source, m).max(end), i)
+            })
+            .collect::<Vec<_>>();
+        statics.sort_unstable();
+
+        Ok(Self {
+            map,
+            statics,
+            modules,
+            max_depth,
+            max_offset,
+            nodes: vec![Node {
+                address: target,
+                offset: 0,
+                parent: 0,
+            }],
+            level: 0..1,
+            current: 0,
+            candidates: 0..0,
+            depth: 1,
+        })
+    }
+
+    /// Indices of the pointers that point at most `max_offset` bytes before `address`.
+    fn candidates(&self, address: usize) -> Range<usize> {
+        let lo = address.saturating_sub(self.max_offset);
+        self.map.partition_point(|&(v, _)| v < lo)..self.map.partition_point(|&(v, _)| v <= address)
+    }
+
+    /// Module containing `address`.
+    fn module_at(&self, address: usize) -> Option<&ModuleInfoWithName> {
+        let i = self
+            .statics
+            .partition_point(|&(start, _, _)| start <= address);
+        let &(_, end, module) = self.statics[..i].last()?;
+        (address < end).then(|| &self.modules[module])
+    }
+}
+
+/// End of the static memory of an ELF module, including `.bss` that is mapped
+/// after the file of the module and isn't a part of it in `/proc/<pid>/maps`.
+#[cfg(unix)]
+fn static_end(source: &impl MemorySource, module: &ModuleInfoWithName) -> usize {
+    use core::{mem::MaybeUninit, slice::from_raw_parts_mut};
+    use libc::{Elf64_Ehdr, Elf64_Phdr, EI_CLASS, ELFCLASS64, PT_LOAD};
+
+    /// Reads a structure of the ELF format.
+    /// # Safety
+    /// Any bit pattern must be a valid `T`.
+    unsafe fn read<T>(source: &impl MemorySource, address: usize) -> Option<T> {
+        let mut value = MaybeUninit::<T>::zeroed();
+        let buf = from_raw_parts_mut(value.as_mut_ptr().cast::<u8>(), size_of::<T>());
+        matches!(source.read_buf(address, buf), Ok(n) if n == buf.len())
+            .then(|| value.assume_init())
+    }
+
+    let base = module.base as usize;
+    let Some(header) = (unsafe { read::<Elf64_Ehdr>(source, base) }) else {
+        return 0;
+    };
+    if header.e_ident[..4] != *b"\x7FELF"
+        || header.e_ident[EI_CLASS] != ELFCLASS64
+        || header.e_phentsize as usize != size_of::<Elf64_Phdr>()
+    {
+        return 0;
+    }
+
+    let (mut start, mut end) = (usize::MAX, 0);
+    for i in 0..header.e_phnum as usize {
+        let address = base + header.e_phoff as usize + i * size_of::<Elf64_Phdr>();
+        let Some(phdr) = (unsafe { read::<Elf64_Phdr>(source, address) }) else {
+            return 0;
+        };
+
+        if phdr.p_type == PT_LOAD {
+            start = start.min(phdr.p_vaddr as usize);
+            end = end.max((phdr.p_vaddr + phdr.p_memsz) as usize);
+        }
+    }
+    end.checked_sub(start).map_or(0, |len| base + len)
+}
+
+/// On windows the size of a module is its `SizeOfImage`, which already covers
+/// the whole image, so statics of the module need no extension.
+#[cfg(not(unix))]
+fn static_end(_: &impl MemorySource, _: &ModuleInfoWithName) -> usize {
+    0
+}
+
+impl Iterator for PathSearch {
+    type Item = PointerChain;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(i) = self.candidates.next() else {
+                if let Some(current) = self.level.next() {
+                    self.current = current;
+                    self.candidates = self.candidates(self.nodes[current].address);
+                    continue;
+                }
+
+                // Nodes added while expanding the previous level make up the next one
+                let start = self.level.end;
+                if self.depth == self.max_depth || start == self.nodes.len() {
+                    return None;
+                }
+
+                self.level = start..self.nodes.len();
+                self.depth += 1;
+                continue;
+            };
+
+            let (value, address) = self.map[i];
+            let offset = self.nodes[self.current].address - value;
+
+            if let Some(module) = self.module_at(address) {
+                let mut offsets = vec![offset];
+                let mut node = self.current;
+                while node != 0 {
+                    offsets.push(self.nodes[node].offset);
+                    node = self.nodes[node].parent;
+                }
+
+                return Some(PointerChain {
+                    module: Some(module.name.clone()),
+                    offset: address - module.base as usize,
+                    offsets,
+                });
+            } else if self.depth < self.max_depth {
+                self.nodes.push(Node {
+                    address,
+                    offset,
+                    parent: self.current,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,270 @@
+extern crate alloc;
+use crate::{read_ranges, MemorySource, CHUNK_SIZE};
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+const MAGIC: &[u8; 8] = b"MFSNAP\0\x01";
+
+/// Contiguous bytes captured by a [`Snapshot`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotRange {
+    /// Address of the first byte.
+    pub address: usize,
+    /// Bytes of memory at the time of the capture.
+    pub bytes: Vec<u8>,
+}
+
+impl SnapshotRange {
+    /// Addresses covered by the range.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.address..self.address + self.bytes.len()
+    }
+}
+
+/// Range of memory that differs between a snapshot and the memory it's compared to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Change {
+    /// Address of the first changed byte.
+    pub address: usize,
+    /// Bytes before the change.
+    pub old: Vec<u8>,
+    /// Bytes after the change.
+    pub new: Vec<u8>,
+}
+
+/// Copy of selected memory of a process at one point in time.
+/// # Behavior
+/// Pages that can't be read during the capture are left out and aren't compared.
+/// ```
+/// # use memflex::{internal::CurrentProcess, Snapshot};
+/// let mut health = Box::new(100_u32);
+/// let address = &*health as *const u32 as usize;
+///
+/// let before = Snapshot::capture(&CurrentProcess, [address..address + 4]);
+/// *health = 90;
+///
+/// let changes = before.diff_live(&CurrentProcess);
+/// // Only the bytes that differ are reported
+/// assert_eq!(changes[0].address, address);
+/// assert_eq!(changes[0].old, [100]);
+/// assert_eq!(changes[0].new, [90]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Sorted by address, never overlapping.
+    ranges: Vec<SnapshotRange>,
+}
+
+impl Snapshot {
+    /// Captures the specified address ranges, overlapping ranges are captured once.
+    pub fn capture(
+        source: &impl MemorySource,
+        ranges: impl IntoIterator<Item = Range<usize>>,
+    ) -> Self {
+        let mut wanted = ranges
+            .into_iter()
+            .filter(|r| r.start < r.end)
+            .collect::<Vec<_>>();
+        wanted.sort_unstable_by_key(|r| r.start);
+
+        let mut merged: Vec<Range<usize>> = vec![];
+        for r in wanted {
+            match merged.last_mut() {
+                Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+                _ => merged.push(r),
+            }
+        }
+
+        let mut snapshot = Self::default();
+        for range in merged {
+            let mut buf = vec![0; range.len()];
+            let read = read_ranges(&mut |a, b| source.read_buf(a, b), range.start, &mut buf);
+
+            snapshot
+                .ranges
+                .extend(read.into_iter().map(|r| SnapshotRange {
+                    address: range.start + r.start,
+                    bytes: buf[r].to_vec(),
+                }));
+        }
+        snapshot
+    }
+
+    /// Captures every writable region of the process.
+    pub fn capture_writable(source: &impl MemorySource) -> crate::Result<Self> {
+        let regions = source.maps()?;
+        Ok(Self::capture(
+            source,
+            regions
+                .into_iter()
+                .filter(|r| r.prot.read() && r.prot.write())
+                .map(|r| r.from..r.to),
+        ))
+    }
+
+    /// Captured ranges, sorted by address.
+    #[inline]
+    pub fn ranges(&self) -> &[SnapshotRange] {
+        &self.ranges
+    }
+
+    /// Total amount of captured bytes.
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(|r| r.bytes.len()).sum()
+    }
+
+    /// Checks if nothing was captured.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns captured bytes at `address`, `None` if any of them weren't captured.
+    pub fn get(&self, address: usize, len: usize) -> Option<&[u8]> {
+        let i = self.ranges.partition_point(|r| r.range().end <= address);
+        let range = self.ranges.get(i)?;
+        let start = address.checked_sub(range.address)?;
+        range.bytes.get(start..start + len)
+    }
+
+    /// Compares the snapshot against a later one, only memory captured by both is compared.
+    /// Returns contiguous changed ranges, sorted by address.
+    pub fn diff(&self, later: &Snapshot) -> Vec<Change> {
+        let mut changes = vec![];
+        let mut others = later.ranges.iter().peekable();
+
+        for old in &self.ranges {
+            while others.next_if(|o| o.range().end <= old.address).is_some() {}
+
+            for new in others.clone().take_while(|o| o.address < old.range().end) {
+                let start = old.address.max(new.address);
+                let end = old.range().end.min(new.range().end);
+                push_diff(
+                    &mut changes,
+                    start,
+                    &old.bytes[start - old.address..end - old.address],
+                    &new.bytes[start - new.address..end - new.address],
+                );
+            }
+        }
+
+        changes
+    }
+
+    /// Compares the snapshot against the current memory of the process,
+    /// memory that can no longer be read isn't compared.
+    /// Returns contiguous changed ranges, sorted by address.
+    pub fn diff_live(&self, source: &impl MemorySource) -> Vec<Change> {
+        let mut changes = vec![];
+        let mut buf = vec![];
+
+        for range in &self.ranges {
+            for (i, old) in range.bytes.chunks(CHUNK_SIZE).enumerate() {
+                let address = range.address + i * CHUNK_SIZE;
+                buf.clear();
+                buf.resize(old.len(), 0);
+
+                for r in read_ranges(&mut |a, b| source.read_buf(a, b), address, &mut buf) {
+                    push_diff(&mut changes, address + r.start, &old[r.clone()], &buf[r]);
+                }
+            }
+        }
+
+        changes
+    }
+
+    /// Serializes the snapshot.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.ranges.len() as u64).to_le_bytes())?;
+        for range in &self.ranges {
+            writer.write_all(&(range.address as u64).to_le_bytes())?;
+            writer.write_all(&(range.bytes.len() as u64).to_le_bytes())?;
+            writer.write_all(&range.bytes)?;
+        }
+        writer.flush()
+    }
+
+    /// Deserializes a snapshot written by [`Snapshot::write_to`].
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Not a memflex snapshot");
+        let read_u64 = |reader: &mut dyn Read| {
+            let mut buf = [0; 8];
+            reader.read_exact(&mut buf)?;
+            usize::try_from(u64::from_le_bytes(buf)).map_err(|_| invalid())
+        };
+
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid());
+        }
+
+        let mut snapshot = Self::default();
+        for _ in 0..read_u64(&mut reader)? {
+            let address = read_u64(&mut reader)?;
+            let len = read_u64(&mut reader)?;
+            if address.checked_add(len).is_none()
+                || snapshot
+                    .ranges
+                    .last()
+                    .is_some_and(|r| r.range().end > address)
+            {
+                return Err(invalid());
+            }
+
+            let mut bytes = vec![];
+            (&mut reader).take(len as u64).read_to_end(&mut bytes)?;
+            if bytes.len() != len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            snapshot.ranges.push(SnapshotRange { address, bytes });
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Writes the snapshot to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Reads a snapshot from a file written by [`Snapshot::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Appends runs of differing bytes, extending the last change if it ends at `address`.
+fn push_diff(changes: &mut Vec<Change>, address: usize, old: &[u8], new: &[u8]) {
+    let mut i = 0;
+    while i < old.len() {
+        if old[i] == new[i] {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < old.len() && old[i] != new[i] {
+            i += 1;
+        }
+
+        match changes.last_mut() {
+            Some(last) if last.address + last.old.len() == address + start => {
+                last.old.extend_from_slice(&old[start..i]);
+                last.new.extend_from_slice(&new[start..i]);
+            }
+            _ => changes.push(Change {
+                address: address + start,
+                old: old[start..i].to_vec(),
+                new: new[start..i].to_vec(),
+            }),
+        }
+    }
+}
@@ -0,0 +1,292 @@
+extern crate alloc;
+use crate::{
+    types::{MemoryRegion, ModuleInfoWithName},
+    MfError, PathSearch, PointerChain, PointerHit,
+};
+use alloc::{vec, vec::Vec};
+use core::{mem::size_of, ops::Range};
+
+pub(crate) const PAGE_SIZE: usize = 0x1000;
+pub(crate) const CHUNK_SIZE: usize = 0x100000;
+
+/// Name matching rule shared by every module lookup,
+/// names are compared exactly on linux and ascii case insensitive on windows.
+pub(crate) fn is_module_name(module: &[u8], name: &str) -> bool {
+    if cfg!(windows) {
+        module.eq_ignore_ascii_case(name.as_bytes())
+    } else {
+        module == name.as_bytes()
+    }
+}
+
+/// Memory of a process that can be read without faulting and enumerated by regions.
+/// Implemented by `OwnedProcess` and by `CurrentProcess` for the process itself.
+pub trait MemorySource {
+    /// Reads process memory, returning amount of bytes read.
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize>;
+
+    /// Returns mapped memory regions of the process.
+    fn maps(&self) -> crate::Result<Vec<MemoryRegion>>;
+
+    /// Returns loaded modules of the process.
+    fn modules(&self) -> crate::Result<Vec<ModuleInfoWithName>>;
+
+    /// Searches for a module by its name.
+    /// # Behavior
+    /// Names are case sensitive on linux and ascii case insensitive on windows,
+    /// the same way the system loader compares them.
+    fn find_module(&self, name: &str) -> crate::Result<ModuleInfoWithName> {
+        self.modules()?
+            .into_iter()
+            .find(|m| is_module_name(m.name.as_bytes(), name))
+            .ok_or(MfError::ModuleNotFound)
+    }
+
+    /// Reads a pointer sized value at `address`.
+    fn read_pointer(&self, address: usize) -> crate::Result<usize> {
+        let mut buf = [0; size_of::<usize>()];
+        match self.read_buf(address, &mut buf) {
+            Ok(n) if n == buf.len() => Ok(usize::from_ne_bytes(buf)),
+            _ => Err(MfError::InvalidAddress(address)),
+        }
+    }
+
+    /// Searches for chains of pointers that start in the memory of a module
+    /// and lead to `target`, shortest chains first.
+    /// # Behavior
+    /// Builds a map of every aligned pointer sized value in readable memory that points
+    /// into readable memory, then walks it backwards from `target`.
+    /// Every pointer of a chain points at most `max_offset` bytes before the next address,
+    /// chains are at most `max_depth` pointers long.
+    /// Amount of chains grows quickly with depth and offset, take as many as needed.
+    fn find_pointer_paths(
+        &self,
+        target: usize,
+        max_depth: usize,
+        max_offset: usize,
+    ) -> crate::Result<impl Iterator<Item = PointerChain>>
+    where
+        Self: Sized,
+    {
+        PathSearch::new(self, target, max_depth, max_offset)
+    }
+
+    /// Finds every aligned pointer sized value in readable regions that points into
+    /// `[target, target + range)`, a `range` of `0` only matches `target` itself.
+    /// # Behavior
+    /// Regions are read lazily in large chunks, pages that can't be read are skipped.
+    /// ```
+    /// # use memflex::{internal::CurrentProcess, MemorySource};
+    /// let target = Box::new(0u64);
+    /// let target = &*target as *const u64 as usize;
+    /// let holder = Box::new(target + 4);
+    ///
+    /// let hit = CurrentProcess
+    ///     .find_pointers(target, 8)
+    ///     .unwrap()
+    ///     .find(|h| h.address == &*holder as *const usize as usize)
+    ///     .unwrap();
+    /// assert_eq!(hit.value - target, 4);
+    /// ```
+    fn find_pointers(
+        &self,
+        target: usize,
+        range: usize,
+    ) -> crate::Result<impl Iterator<Item = PointerHit> + '_>
+    where
+        Self: Sized,
+    {
+        let mut chunks = RegionChunks::new(self, self.maps()?, 0);
+        let mut pos = 0;
+
+        Ok(core::iter::from_fn(move || loop {
+            let data = chunks.data();
+            while let Some(value) = data.get(pos..pos + size_of::<usize>()) {
+                let address = chunks.base() + pos;
+                pos += size_of::<usize>();
+
+                let value = usize::from_ne_bytes(value.try_into().unwrap());
+                if value.wrapping_sub(target) < range.max(1) {
+                    return Some(PointerHit {
+                        address,
+                        value,
+                        region: chunks.region(),
+                    });
+                }
+            }
+
+            if !chunks.advance() {
+                return None;
+            }
+            pos = chunks.base().wrapping_neg() % size_of::<usize>();
+        }))
+    }
+}
+
+/// Memory of a process that can be written to.
+/// Implemented by `OwnedProcess` and by `CurrentProcess` for the process itself.
+pub trait MemorySink: MemorySource {
+    /// Writes process memory, returning amount of bytes written.
+    fn write_buf(&self, address: usize, buf: &[u8]) -> crate::Result<usize>;
+}
+
+/// Reads `buf.len()` bytes at `address`, returning amount of contiguous bytes that could be read.
+/// Falls back to reading a single page if the whole buffer can't be read at once.
+pub(crate) fn read_available(
+    read: &mut impl FnMut(usize, &mut [u8]) -> crate::Result<usize>,
+    address: usize,
+    buf: &mut [u8],
+) -> usize {
+    match read(address, buf) {
+        Ok(n) if n > 0 => n,
+        _ => {
+            let page_end = (address / PAGE_SIZE + 1) * PAGE_SIZE;
+            let len = buf.len().min(page_end - address);
+            read(address, &mut buf[..len]).unwrap_or(0)
+        }
+    }
+}
+
+/// Reads `buf.len()` bytes at `start` in chunks, returning ranges of `buf` that could be read.
+/// Pages that can't be read are left untouched.
+pub(crate) fn read_ranges(
+    read: &mut impl FnMut(usize, &mut [u8]) -> crate::Result<usize>,
+    start: usize,
+    buf: &mut [u8],
+) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut offset = 0;
+    while offset < buf.len() {
+        let address = start + offset;
+        let want = (CHUNK_SIZE - address % CHUNK_SIZE).min(buf.len() - offset);
+        match read_available(read, address, &mut buf[offset..offset + want]) {
+            0 => offset += (PAGE_SIZE - address % PAGE_SIZE).min(buf.len() - offset),
+            read => {
+                match ranges.last_mut() {
+                    Some(last) if last.end == offset => last.end += read,
+                    _ => ranges.push(offset..offset + read),
+                }
+                offset += read;
+            }
+        }
+    }
+    ranges
+}
+
+/// Reads readable regions of a process in chunks, pages that can't be read are skipped.
+/// Consecutive chunks of a region overlap by `overlap` bytes if they are contiguous.
+pub(crate) struct RegionChunks<'a, S: ?Sized> {
+    source: &'a S,
+    regions: vec::IntoIter<MemoryRegion>,
+    region: Option<MemoryRegion>,
+    buf: Vec<u8>,
+    /// Address of `buf[0]`.
+    base: usize,
+    /// Amount of valid bytes in `buf`.
+    filled: usize,
+    /// Address of the next chunk to read.
+    cursor: usize,
+    overlap: usize,
+}
+
+impl<'a, S: MemorySource + ?Sized> RegionChunks<'a, S> {
+    /// Reads the readable ones of `regions`.
+    pub fn new(source: &'a S, mut regions: Vec<MemoryRegion>, overlap: usize) -> Self {
+        regions.retain(|r| r.prot.read() && r.to > r.from);
+
+        Self {
+            source,
+            regions: regions.into_iter(),
+            region: None,
+            buf: vec![0; CHUNK_SIZE + overlap],
+            base: 0,
+            filled: 0,
+            cursor: 0,
+            overlap,
+        }
+    }
+
+    /// Reads the next chunk, returns `false` when all regions are exhausted.
+    pub fn advance(&mut self) -> bool {
+        loop {
+            let region = match self.region {
+                Some(r) if self.cursor < r.to => r,
+                _ => {
+                    let Some(r) = self.regions.next() else {
+                        return false;
+                    };
+                    self.region = Some(r);
+                    self.cursor = r.from;
+                    self.filled = 0;
+                    continue;
+                }
+            };
+
+            let keep = if self.base + self.filled == self.cursor {
+                self.filled.min(self.overlap)
+            } else {
+                0
+            };
+            self.buf.copy_within(self.filled - keep..self.filled, 0);
+
+            let chunk_end = ((self.cursor / CHUNK_SIZE + 1) * CHUNK_SIZE).min(region.to);
+            let source = self.source;
+            let read = read_available(
+                &mut |address, buf| source.read_buf(address, buf),
+                self.cursor,
+                &mut self.buf[keep..keep + chunk_end - self.cursor],
+            );
+
+            if read == 0 {
+                self.cursor = (self.cursor / PAGE_SIZE + 1) * PAGE_SIZE;
+                self.filled = 0;
+                continue;
+            }
+
+            self.base = self.cursor - keep;
+            self.filled = keep + read;
+            self.cursor += read;
+            return true;
+        }
+    }
+
+    /// Bytes of the current chunk.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.buf[..self.filled]
+    }
+
+    /// Address of the first byte of the current chunk.
+    #[inline]
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Region the current chunk belongs to.
+    #[inline]
+    pub fn region(&self) -> MemoryRegion {
+        self.region.unwrap_or_default()
+    }
+}
+
+/// Parses `/proc/<pid>/maps`.
+#[cfg(unix)]
+pub(crate) fn proc_maps(pid: u32) -> crate::Result<Vec<MemoryRegion>> {
+    use crate::{types::Protection, MfError};
+
+    Ok(std::fs::read_to_string(alloc::format!("/proc/{pid}/maps"))
+        .map_err(|_| MfError::ProcessDied)?
+        .lines()
+        .map(|l| {
+            let mut iter = l.split(' ');
+            let (from, to) = iter.next().unwrap().split_once('-').unwrap();
+
+            let from = usize::from_str_radix(from, 16).unwrap();
+            let to = usize::from_str_radix(to, 16).unwrap();
+
+            let prot = Protection::parse(&iter.next().unwrap()[0..3]);
+
+            MemoryRegion { from, to, prot }
+        })
+        .collect())
+}
@@ -0,0 +1,58 @@
+#![allow(missing_docs)]
+
+/// Entry of the `PT_DYNAMIC` segment.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ElfDyn {
+    pub tag: i64,
+    pub val: u64,
+}
+
+pub const DT_NULL: i64 = 0;
+pub const DT_PLTRELSZ: i64 = 2;
+pub const DT_STRTAB: i64 = 5;
+pub const DT_SYMTAB: i64 = 6;
+pub const DT_RELA: i64 = 7;
+pub const DT_RELASZ: i64 = 8;
+pub const DT_SONAME: i64 = 14;
+pub const DT_JMPREL: i64 = 23;
+
+/// Relocation with an addend, entry of `DT_RELA` and `DT_JMPREL` tables.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ElfRela {
+    pub offset: u64,
+    pub info: u64,
+    pub addend: i64,
+}
+
+impl ElfRela {
+    /// Index of the symbol in `DT_SYMTAB`.
+    #[inline]
+    pub fn symbol(&self) -> usize {
+        (self.info >> 32) as usize
+    }
+
+    /// Type of the relocation.
+    #[inline]
+    pub fn kind(&self) -> u32 {
+        self.info as u32
+    }
+}
+
+/// Entry of the `DT_SYMTAB` table.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ElfSym {
+    pub name: u32,
+    pub info: u8,
+    pub other: u8,
+    pub shndx: u16,
+    pub value: u64,
+    pub size: u64,
+}
+
+pub const R_X86_64_GLOB_DAT: u32 = 6;
+pub const R_X86_64_JUMP_SLOT: u32 = 7;
+pub const R_AARCH64_GLOB_DAT: u32 = 1025;
+pub const R_AARCH64_JUMP_SLOT: u32 = 1026;
@@ -9,6 +9,10 @@ pub use prot::*;
 #[cfg(windows)]
 pub mod win;
 
+/// ELF datatypes
+#[cfg(unix)]
+pub mod elf;
+
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
@@ -46,3 +50,14 @@ impl From<&windows::Win32::System::Diagnostics::ToolHelp::MODULEENTRY32W> for Mo
         }
     }
 }
+
+/// Represents a chunk of mapped memory in a process
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRegion {
+    /// Start
+    pub from: usize,
+    /// End
+    pub to: usize,
+    /// Prtection
+    pub prot: Protection,
+}
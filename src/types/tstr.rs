@@ -36,6 +36,20 @@ impl TStr {
         core::str::from_utf8_unchecked(terminated_array::<u8>(self.ptr.as_ptr() as _, 0))
     }
 
+    /// Converts [`TStr`] to string slice, checking that the memory can be read.
+    /// # Errors
+    /// * [`crate::MfError::InvalidAddress`] if the string can't be read.
+    /// * [`crate::MfError::TerminatorNotFound`] if the string is longer than `max_len` bytes.
+    /// * [`crate::MfError::InvalidString`] if the string isn't valid UTF-8.
+    /// # Safety
+    /// * Memory of the string must stay mapped and unchanged for `'a`.
+    #[cfg(feature = "internal")]
+    pub unsafe fn try_as_str<'a>(&self, max_len: usize) -> crate::Result<&'a str> {
+        let bytes =
+            crate::internal::try_terminated_array::<u8>(self.ptr.as_ptr() as _, 0, max_len)?;
+        core::str::from_utf8(bytes).map_err(|_| crate::MfError::InvalidString)
+    }
+
     /// Converts [`TStr`] into signed byte slice.
     /// # Safety
     /// * [`TStr`] must be a valid pointer.
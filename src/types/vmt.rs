@@ -1,4 +1,4 @@
-use core::slice::from_raw_parts;
+use core::{marker::PhantomData, slice::from_raw_parts};
 
 /// Virtual method table pointer
 /// ```
@@ -38,7 +38,7 @@ use core::slice::from_raw_parts;
 /// ```
 #[repr(transparent)]
 pub struct VmtPtr {
-    vmt: *const usize,
+    pub(crate) vmt: *const usize,
 }
 
 impl VmtPtr {
@@ -64,3 +64,34 @@ impl VmtPtr {
         self.vmt.add(idx).cast::<T>().read()
     }
 }
+
+/// Index of a function in a virtual method table with the type of the function,
+/// generated by [`crate::interface!`] for every virtual function.
+pub struct VmtEntry<F> {
+    index: usize,
+    _ph: PhantomData<F>,
+}
+
+impl<F> VmtEntry<F> {
+    /// Entry at `index`, `F` is the type of the function pointer.
+    pub const fn new(index: usize) -> Self {
+        Self {
+            index,
+            _ph: PhantomData,
+        }
+    }
+
+    /// Index of the function in the table.
+    #[inline]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<F> Clone for VmtEntry<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for VmtEntry<F> {}
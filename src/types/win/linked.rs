@@ -10,11 +10,7 @@ pub struct ListEntry<const OFFSET: usize, T> {
 
 impl<const OFFSET: usize, T> Clone for ListEntry<OFFSET, T> {
     fn clone(&self) -> Self {
-        Self {
-            _ty: PhantomData,
-            next: self.next,
-            prev: self.prev,
-        }
+        *self
     }
 }
 impl<const OFFSET: usize, T> Copy for ListEntry<OFFSET, T> {}
@@ -45,13 +41,18 @@ impl<const OFFSET: usize, T> ListEntry<OFFSET, T> {
     }
 
     /// Assuming `self` is a list head, iterate over immutable references to all items in the list.
-    pub unsafe fn iter<'r>(&self) -> impl Iterator<Item = &'r T> {
+    /// # Safety
+    /// * `self` must be the head of a valid circular list of `T` entries.
+    pub unsafe fn iter<'r>(&self) -> impl Iterator<Item = &'r T>
+    where
+        T: 'r,
+    {
         let last = self.prev.map(|v| v.as_ptr().cast_const());
         let mut current = last;
         core::iter::from_fn(move || unsafe {
             let r = (*current?).next()?.as_ref();
             current = Some((*current?).next?.as_ptr().cast_const());
-            if current? as usize == last? as usize {
+            if core::ptr::eq(current?, last?) {
                 current = None;
             }
 
@@ -60,13 +61,18 @@ impl<const OFFSET: usize, T> ListEntry<OFFSET, T> {
     }
 
     /// Assuming `self` is a list head, iterate over mutable references to all items in the list.
-    pub unsafe fn iter_mut<'r>(&mut self) -> impl Iterator<Item = &'r mut T> {
+    /// # Safety
+    /// * `self` must be the head of a valid circular list of `T` entries.
+    pub unsafe fn iter_mut<'r>(&mut self) -> impl Iterator<Item = &'r mut T>
+    where
+        T: 'r,
+    {
         let last = self.prev.map(|v| v.as_ptr());
         let mut current = last;
         core::iter::from_fn(move || unsafe {
             let r = (*current?).next()?.as_mut();
             current = Some((*current?).next?.as_ptr());
-            if current? as usize == last? as usize {
+            if core::ptr::eq(current?, last?) {
                 current = None;
             }
 
@@ -79,7 +85,7 @@ impl<const OFFSET: usize, T> ListEntry<OFFSET, T> {
 mod tests {
     use super::ListEntry;
     use crate::assert_offset;
-    use core::ptr::NonNull;
+    use core::ptr::{addr_of_mut, NonNull};
 
     #[repr(C)]
     struct Value {
@@ -104,17 +110,19 @@ mod tests {
                 next: ListEntry::null(),
             };
 
-            a.next.prev = Some(NonNull::new_unchecked(&mut c.next));
-            a.next.next = Some(NonNull::new_unchecked(&mut b.next));
-
-            b.next.prev = Some(NonNull::new_unchecked(&mut a.next));
-            b.next.next = Some(NonNull::new_unchecked(&mut c.next));
-
-            c.next.prev = Some(NonNull::new_unchecked(&mut b.next));
-            c.next.next = Some(NonNull::new_unchecked(&mut a.next));
+            // Links are written through raw pointers, references to the entries would alias them
+            let entries = [
+                addr_of_mut!(a.next),
+                addr_of_mut!(b.next),
+                addr_of_mut!(c.next),
+            ];
+            for (i, &entry) in entries.iter().enumerate() {
+                (*entry).next = NonNull::new(entries[(i + 1) % 3]);
+                (*entry).prev = NonNull::new(entries[(i + 2) % 3]);
+            }
 
             assert_eq!(
-                &a.next.iter().map(|v| v.value).collect::<Vec<_>>(),
+                &(*entries[0]).iter().map(|v| v.value).collect::<Vec<_>>(),
                 &[1, 2, 3]
             );
         }
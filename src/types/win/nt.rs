@@ -46,6 +46,9 @@ assert_offset!(
 );
 
 impl Teb {
+    /// Returns the TEB of the current thread.
+    /// # Safety
+    /// * Must be called on x86_64 windows, where `gs:[0x30]` points to the TEB.
     pub unsafe fn get<'r>() -> &'r Teb {
         let mut out: *const Teb;
         core::arch::asm! {
@@ -55,6 +58,9 @@ impl Teb {
         &*out
     }
 
+    /// Returns the TEB of the current thread.
+    /// # Safety
+    /// * Same as [`Teb::get`], the TEB must not be borrowed elsewhere.
     pub unsafe fn get_mut<'r>() -> &'r mut Teb {
         let mut out: *mut Teb;
         core::arch::asm! {
@@ -66,6 +72,9 @@ impl Teb {
 }
 
 impl PebLdrData {
+    /// Iterates over the loaded modules in memory order.
+    /// # Safety
+    /// * The loader lists must not be modified while iterating.
     pub unsafe fn iter(&self) -> impl Iterator<Item = &LdrDataTableEntry> + '_ {
         self.in_memory_order_list.iter()
     }
@@ -0,0 +1,526 @@
+extern crate alloc;
+use crate::{read_ranges, MemorySource, MfError, RegionChunks, Scanner, PAGE_SIZE};
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/// Type of the values a [`ValueScan`] looks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    #[allow(missing_docs)]
+    U8,
+    #[allow(missing_docs)]
+    U16,
+    #[allow(missing_docs)]
+    U32,
+    #[allow(missing_docs)]
+    U64,
+    #[allow(missing_docs)]
+    I8,
+    #[allow(missing_docs)]
+    I16,
+    #[allow(missing_docs)]
+    I32,
+    #[allow(missing_docs)]
+    I64,
+    #[allow(missing_docs)]
+    F32,
+    #[allow(missing_docs)]
+    F64,
+    /// Byte string of the specified length.
+    Bytes(usize),
+}
+
+impl ValueKind {
+    /// Size of a value in bytes.
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::U8 | Self::I8 => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 | Self::F32 => 4,
+            Self::U64 | Self::I64 | Self::F64 => 8,
+            Self::Bytes(len) => *len,
+        }
+    }
+
+    /// Checks if the kind is `F32` or `F64`.
+    pub const fn is_float(&self) -> bool {
+        matches!(self, Self::F32 | Self::F64)
+    }
+}
+
+/// Value of a [`ValueKind`].
+#[derive(Debug, Clone, PartialEq)]
+#[allow(missing_docs)]
+pub enum Value {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Bytes(Vec<u8>),
+}
+
+macro_rules! numbers {
+    ($($variant:ident($ty:ty)),*) => {
+        impl Value {
+            /// Kind of the value.
+            pub fn kind(&self) -> ValueKind {
+                match self {
+                    $(Self::$variant(_) => ValueKind::$variant,)*
+                    Self::Bytes(b) => ValueKind::Bytes(b.len()),
+                }
+            }
+
+            /// Bytes of the value in native byte order.
+            pub fn to_bytes(&self) -> Vec<u8> {
+                match self {
+                    $(Self::$variant(v) => v.to_ne_bytes().to_vec(),)*
+                    Self::Bytes(b) => b.clone(),
+                }
+            }
+
+            /// Reads a value of the specified kind from bytes in native byte order.
+            /// # Panics
+            /// If `bytes` is shorter than [`ValueKind::size`].
+            pub fn from_bytes(kind: ValueKind, bytes: &[u8]) -> Self {
+                let bytes = &bytes[..kind.size()];
+                match kind {
+                    $(ValueKind::$variant => Self::$variant(<$ty>::from_ne_bytes(bytes.try_into().unwrap())),)*
+                    ValueKind::Bytes(_) => Self::Bytes(bytes.to_vec()),
+                }
+            }
+        }
+
+        /// Numeric value of `bytes`, `None` for byte strings.
+        fn number(kind: ValueKind, bytes: &[u8]) -> Option<Number> {
+            let bytes = &bytes[..kind.size()];
+            match kind {
+                $(ValueKind::$variant => Some(<$ty>::from_ne_bytes(bytes.try_into().unwrap()).into_number()),)*
+                ValueKind::Bytes(_) => None,
+            }
+        }
+
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+numbers!(
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64)
+);
+
+impl From<&[u8]> for Value {
+    fn from(value: &[u8]) -> Self {
+        Self::Bytes(value.to_vec())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Number {
+    Int(i128),
+    Float(f64),
+}
+
+trait IntoNumber {
+    fn into_number(self) -> Number;
+}
+
+macro_rules! into_number {
+    ($variant:ident: $($ty:ty),*) => {
+        $(
+            impl IntoNumber for $ty {
+                fn into_number(self) -> Number {
+                    Number::$variant(self as _)
+                }
+            }
+        )*
+    };
+}
+
+into_number!(Int: u8, u16, u32, u64, i8, i16, i32, i64);
+into_number!(Float: f32, f64);
+
+impl Number {
+    fn float(self) -> f64 {
+        match self {
+            Number::Int(v) => v as f64,
+            Number::Float(v) => v,
+        }
+    }
+
+    fn int(self) -> i128 {
+        match self {
+            Number::Int(v) => v,
+            Number::Float(v) => v as i128,
+        }
+    }
+}
+
+/// Condition of a next scan, compares the current value of every result
+/// with the value it had during the previous scan.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanCondition {
+    /// Value equals the specified one.
+    Exact(Value),
+    /// Value is different.
+    Changed,
+    /// Value is the same.
+    Unchanged,
+    /// Value is greater.
+    Increased,
+    /// Value is less.
+    Decreased,
+    /// Value changed by exactly the specified amount, `new - old`.
+    /// Integers wrap around, so `U32(u32::MAX)` is a decrease by one.
+    Delta(Value),
+}
+
+/// Options of a [`ValueScan`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ValueScanOptions {
+    /// Alignment of the values, `0` aligns numbers to their size and doesn't align byte strings.
+    pub align: usize,
+    /// Largest difference between floats that are considered equal.
+    pub tolerance: f64,
+}
+
+/// Offsets of the results in a block.
+enum Hits {
+    List(Vec<u32>),
+    /// One bit for every aligned offset.
+    Bitmap {
+        bits: Vec<u64>,
+        len: usize,
+    },
+}
+
+impl Hits {
+    /// Stores ascending offsets that are multiples of `align` in the smaller representation.
+    fn new(offsets: Vec<u32>, align: usize) -> Self {
+        let positions = offsets.last().map_or(0, |&o| o as usize / align + 1);
+        if positions.div_ceil(64) * 8 >= offsets.len() * 4 {
+            return Self::List(offsets);
+        }
+
+        let mut bits = vec![0_u64; positions.div_ceil(64)];
+        for &o in &offsets {
+            let i = o as usize / align;
+            bits[i / 64] |= 1 << (i % 64);
+        }
+
+        Self::Bitmap {
+            bits,
+            len: offsets.len(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::List(offsets) => offsets.len(),
+            Self::Bitmap { len, .. } => *len,
+        }
+    }
+
+    fn iter(&self, align: usize) -> Box<dyn Iterator<Item = usize> + '_> {
+        match self {
+            Self::List(offsets) => Box::new(offsets.iter().map(|&o| o as usize)),
+            Self::Bitmap { bits, .. } => {
+                Box::new(bits.iter().enumerate().flat_map(move |(i, &w)| {
+                    let mut word = w;
+                    core::iter::from_fn(move || {
+                        let bit = (word != 0).then(|| word.trailing_zeros() as usize)?;
+                        word &= word - 1;
+                        Some((i * 64 + bit) * align)
+                    })
+                }))
+            }
+        }
+    }
+}
+
+/// Results found in a chunk of memory.
+struct Block {
+    /// Aligned address the offsets are relative to.
+    base: usize,
+    hits: Hits,
+    /// Values of the results during the last scan, [`ValueKind::size`] bytes each.
+    values: Vec<u8>,
+}
+
+/// Scan for values in writable memory that is narrowed down by subsequent scans.
+/// # Behavior
+/// Results are stored per chunk of memory, with their offsets either as a list
+/// or as a bitmap, whichever is smaller, along with the values they had during the last scan.
+/// ```
+/// # use memflex::{internal::CurrentProcess, ScanCondition, ValueScan, ValueScanOptions};
+/// let mut health = Box::new(0x4D46_1337_u32);
+/// let address = &*health as *const u32 as usize;
+///
+/// let mut scan = ValueScan::exact(&CurrentProcess, 0x4D46_1337_u32.into(), Default::default()).unwrap();
+/// assert!(scan.results().any(|(a, _)| a == address));
+///
+/// *health -= 10;
+/// scan.next_scan(&CurrentProcess, &ScanCondition::Decreased).unwrap();
+/// scan.next_scan(&CurrentProcess, &ScanCondition::Exact(0x4D46_132D_u32.into())).unwrap();
+/// assert!(scan.results().any(|(a, _)| a == address));
+/// # drop(health);
+/// ```
+pub struct ValueScan {
+    kind: ValueKind,
+    align: usize,
+    tolerance: f64,
+    blocks: Vec<Block>,
+}
+
+impl ValueScan {
+    /// First scan for values equal to `value`, floats are compared with
+    /// [`ValueScanOptions::tolerance`].
+    /// Returns [`MfError::InvalidValue`] if `value` is an empty byte string.
+    pub fn exact(
+        source: &impl MemorySource,
+        value: Value,
+        options: ValueScanOptions,
+    ) -> crate::Result<Self> {
+        Self::first(source, value.kind(), options, Some(&value))
+    }
+
+    /// First scan that keeps every value of the kind, to be narrowed down by next scans.
+    /// # Behavior
+    /// Stores a copy of all writable memory, or more if values are aligned below their size.
+    /// # Errors
+    /// [`MfError::InvalidValue`] if `kind` is an empty byte string.
+    pub fn unknown(
+        source: &impl MemorySource,
+        kind: ValueKind,
+        options: ValueScanOptions,
+    ) -> crate::Result<Self> {
+        Self::first(source, kind, options, None)
+    }
+
+    fn first(
+        source: &impl MemorySource,
+        kind: ValueKind,
+        options: ValueScanOptions,
+        value: Option<&Value>,
+    ) -> crate::Result<Self> {
+        let size = kind.size();
+        if size == 0 {
+            return Err(MfError::InvalidValue);
+        }
+
+        let mut scan = Self {
+            kind,
+            align: match (options.align, kind) {
+                (0, ValueKind::Bytes(_)) => 1,
+                (0, _) => size,
+                (align, _) => align,
+            },
+            tolerance: options.tolerance,
+            blocks: vec![],
+        };
+
+        let mut regions = source.maps()?;
+        regions.retain(|r| r.prot.write());
+        let mut chunks = RegionChunks::new(source, regions, size - 1);
+
+        let needle = value.map(Value::to_bytes);
+        let scanner = needle
+            .as_deref()
+            .filter(|_| !kind.is_float())
+            .map(Scanner::new);
+        let float = value.filter(|_| kind.is_float()).map(|v| v.to_bytes());
+
+        while chunks.advance() {
+            let (data, base) = (chunks.data(), chunks.base());
+            let block = base / scan.align * scan.align;
+            let (mut offsets, mut values) = (vec![], vec![]);
+            let mut push = |pos: usize| {
+                offsets.push((base + pos - block) as u32);
+                values.extend_from_slice(&data[pos..pos + size]);
+            };
+
+            if let Some(scanner) = &scanner {
+                let mut from = 0;
+                while let Some(pos) =
+                    scanner.search(data, from..usize::MAX, true, false, base, scan.align)
+                {
+                    push(pos);
+                    from = pos + 1;
+                }
+            } else {
+                let first = base.wrapping_neg() % scan.align;
+                for pos in (first..(data.len() + 1).saturating_sub(size)).step_by(scan.align) {
+                    if float
+                        .as_ref()
+                        .is_none_or(|v| scan.float_eq(&data[pos..], v, 0.))
+                    {
+                        push(pos);
+                    }
+                }
+            }
+
+            if !offsets.is_empty() {
+                scan.blocks.push(Block {
+                    base: block,
+                    hits: Hits::new(offsets, scan.align),
+                    values,
+                });
+            }
+        }
+
+        Ok(scan)
+    }
+
+    /// Compares `new - old` against `expected` with the tolerance of the scan.
+    fn float_eq(&self, new: &[u8], expected: &[u8], old: f64) -> bool {
+        let new = number(self.kind, new).unwrap().float();
+        let expected = number(self.kind, expected).unwrap().float();
+        (new - old - expected).abs() <= self.tolerance
+    }
+
+    /// Keeps the results whose current value satisfies `condition`,
+    /// results that can no longer be read are dropped.
+    /// # Errors
+    /// [`MfError::InvalidValue`], leaving the results untouched:
+    /// * If the value of the condition is of a different kind than the scan.
+    /// * If `Increased`, `Decreased` or `Delta` is used on byte strings.
+    pub fn next_scan(
+        &mut self,
+        source: &impl MemorySource,
+        condition: &ScanCondition,
+    ) -> crate::Result<()> {
+        let kind = self.kind;
+        if let ScanCondition::Exact(v) | ScanCondition::Delta(v) = condition {
+            if v.kind() != kind {
+                return Err(MfError::InvalidValue);
+            }
+        }
+        if let ScanCondition::Increased | ScanCondition::Decreased | ScanCondition::Delta(_) =
+            condition
+        {
+            if matches!(kind, ValueKind::Bytes(_)) {
+                return Err(MfError::InvalidValue);
+            }
+        }
+
+        let expected = match condition {
+            ScanCondition::Exact(v) | ScanCondition::Delta(v) => v.to_bytes(),
+            _ => vec![],
+        };
+        let keep = |old: &[u8], new: &[u8]| match condition {
+            ScanCondition::Exact(_) if kind.is_float() => self.float_eq(new, &expected, 0.),
+            ScanCondition::Exact(_) => new == expected,
+            ScanCondition::Changed => old != new,
+            ScanCondition::Unchanged => old == new,
+            ScanCondition::Increased => number(kind, new) > number(kind, old),
+            ScanCondition::Decreased => number(kind, new) < number(kind, old),
+            ScanCondition::Delta(_) if kind.is_float() => {
+                self.float_eq(new, &expected, number(kind, old).unwrap().float())
+            }
+            ScanCondition::Delta(_) => {
+                let [new, old, delta] =
+                    [new, old, &expected].map(|b| number(kind, b).unwrap().int());
+                (new - old - delta).rem_euclid(1 << (kind.size() * 8)) == 0
+            }
+        };
+
+        let size = kind.size();
+        let mut buf = vec![];
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|block| {
+                let all = block.hits.iter(self.align).collect::<Vec<_>>();
+                let (mut offsets, mut values) = (vec![], vec![]);
+
+                // Reads results that are close to each other at once.
+                let mut i = 0;
+                while i < all.len() {
+                    let start = all[i];
+                    let mut end = i + 1;
+                    while end < all.len() && all[end] < all[end - 1] + size + PAGE_SIZE {
+                        end += 1;
+                    }
+
+                    buf.clear();
+                    buf.resize(all[end - 1] + size - start, 0);
+                    let ranges = read_ranges(
+                        &mut |a, b| source.read_buf(a, b),
+                        block.base + start,
+                        &mut buf,
+                    );
+
+                    let mut ranges = ranges.iter().peekable();
+                    for (j, &offset) in all.iter().enumerate().take(end).skip(i) {
+                        let pos = offset - start;
+                        while ranges.next_if(|r| r.end < pos + size).is_some() {}
+                        if ranges.peek().is_none_or(|r| r.start > pos) {
+                            continue;
+                        }
+
+                        let (old, new) = (&block.values[j * size..][..size], &buf[pos..pos + size]);
+                        if keep(old, new) {
+                            offsets.push(offset as u32);
+                            values.extend_from_slice(new);
+                        }
+                    }
+                    i = end;
+                }
+
+                Block {
+                    base: block.base,
+                    hits: Hits::new(offsets, self.align),
+                    values,
+                }
+            })
+            .filter(|b| b.hits.len() != 0)
+            .collect();
+
+        self.blocks = blocks;
+        Ok(())
+    }
+
+    /// Kind of the values.
+    pub fn kind(&self) -> ValueKind {
+        self.kind
+    }
+
+    /// Amount of results.
+    pub fn len(&self) -> usize {
+        self.blocks.iter().map(|b| b.hits.len()).sum()
+    }
+
+    /// Checks if nothing was found.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Addresses of the results with their values during the last scan, in ascending order.
+    pub fn results(&self) -> impl Iterator<Item = (usize, Value)> + '_ {
+        let size = self.kind.size();
+        self.blocks.iter().flat_map(move |b| {
+            b.hits.iter(self.align).enumerate().map(move |(i, offset)| {
+                let value = Value::from_bytes(self.kind, &b.values[i * size..]);
+                (b.base + offset, value)
+            })
+        })
+    }
+}
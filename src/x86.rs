@@ -0,0 +1,441 @@
+//! Minimal x86-64 instruction length decoder.
+#[cfg(all(feature = "internal", target_arch = "x86_64"))]
+extern crate alloc;
+#[cfg(all(feature = "internal", target_arch = "x86_64"))]
+use alloc::vec::Vec;
+
+/// Kind of a relative branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Branch {
+    Jmp,
+    Call,
+    /// Conditional jump with its condition code.
+    Jcc(u8),
+    /// `loop`, `loope`, `loopne` and `jrcxz`, only exist with rel8.
+    Loop,
+}
+
+/// Decoded instruction, offsets are relative to its first byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Instruction {
+    pub len: usize,
+    /// Offset and size of the memory displacement.
+    pub disp: Option<(usize, usize)>,
+    /// Offset and size of the immediate operand.
+    pub imm: Option<(usize, usize)>,
+    /// Displacement is relative to the end of the instruction.
+    pub rip_relative: bool,
+    /// Immediate operand is a branch displacement.
+    pub branch: Option<Branch>,
+}
+
+const MAX_LEN: usize = 15;
+
+/// Immediate operand of an opcode.
+#[derive(Clone, Copy)]
+enum Imm {
+    None,
+    Byte,
+    Word,
+    /// 16 or 32 bits depending on the operand size.
+    Z,
+    /// 16, 32 or 64 bits depending on the operand size (`mov r64, imm64`).
+    V,
+    /// `enter imm16, imm8`
+    Enter,
+    /// Memory offset, 64 or 32 bits depending on the address size.
+    Offset,
+}
+
+/// Decodes the instruction at the start of `code`.
+/// Returns `None` if the instruction is invalid in 64 bit mode or `code` is too short.
+pub(crate) fn decode(code: &[u8]) -> Option<Instruction> {
+    let code = &code[..code.len().min(MAX_LEN)];
+    let mut i = 0;
+    let (mut opsize, mut adsize, mut rex_w) = (false, false, false);
+
+    loop {
+        match *code.get(i)? {
+            0x66 => opsize = true,
+            0x67 => adsize = true,
+            0xF0 | 0xF2 | 0xF3 | 0x26 | 0x2E | 0x36 | 0x3E | 0x64 | 0x65 => {}
+            _ => break,
+        }
+        i += 1;
+    }
+
+    if code[i] & 0xF0 == 0x40 {
+        rex_w = code[i] & 8 != 0;
+        i += 1;
+    }
+
+    let op = *code.get(i)?;
+    i += 1;
+
+    let (modrm, imm, branch) = match op {
+        0xC4 | 0xC5 | 0x62 => {
+            // VEX and EVEX prefixes, map select is in the first payload byte.
+            let (map, payload) = match op {
+                0xC5 => (1, 1),
+                0xC4 => (*code.get(i)? & 0x1F, 2),
+                _ => (*code.get(i)? & 0x07, 3),
+            };
+            if op != 0xC5 {
+                rex_w = *code.get(i + 1)? & 0x80 != 0;
+            }
+            i += payload;
+            let op = *code.get(i)?;
+            i += 1;
+
+            match map {
+                1 if op == 0x77 => (false, Imm::None, None),
+                1 => (true, two_byte_imm(op), None),
+                2 => (true, Imm::None, None),
+                3 => (true, Imm::Byte, None),
+                _ => return None,
+            }
+        }
+        0x0F => {
+            let op = *code.get(i)?;
+            i += 1;
+            match op {
+                0x38 => {
+                    i += 1;
+                    (true, Imm::None, None)
+                }
+                0x3A => {
+                    i += 1;
+                    (true, Imm::Byte, None)
+                }
+                0x80..=0x8F => (false, Imm::Z, Some(Branch::Jcc(op & 0xF))),
+                0x04 | 0x0A | 0x0C | 0x24..=0x27 | 0x36 | 0x39 | 0x3B..=0x3F => return None,
+                0x05..=0x09 | 0x0B | 0x0E | 0x30..=0x35 | 0x37 | 0x77 | 0xA0..=0xA2 => {
+                    (false, Imm::None, None)
+                }
+                0xA8..=0xAA | 0xC8..=0xCF => (false, Imm::None, None),
+                // 3DNow! has its opcode in the place of an immediate.
+                0x0F => (true, Imm::Byte, None),
+                _ => (true, two_byte_imm(op), None),
+            }
+        }
+        0x06 | 0x07 | 0x0E | 0x16 | 0x17 | 0x1E | 0x1F | 0x27 | 0x2F | 0x37 | 0x3F => return None,
+        0x60 | 0x61 | 0x82 | 0x9A | 0xCE | 0xD4 | 0xD5 | 0xD6 | 0xEA => return None,
+        0x00..=0x3F => match op & 7 {
+            4 => (false, Imm::Byte, None),
+            5 => (false, Imm::Z, None),
+            _ => (true, Imm::None, None),
+        },
+        0x50..=0x5F | 0x6C..=0x6F | 0x90..=0x99 | 0x9B..=0x9F | 0xA4..=0xA7 | 0xAA..=0xAF => {
+            (false, Imm::None, None)
+        }
+        0x63 | 0x84..=0x8F | 0xD0..=0xD3 | 0xD8..=0xDF | 0xFE | 0xFF => (true, Imm::None, None),
+        0x68 => (false, Imm::Z, None),
+        0x69 | 0x81 | 0xC7 => (true, Imm::Z, None),
+        0x6A | 0xA8 | 0xB0..=0xB7 | 0xCD | 0xE4..=0xE7 => (false, Imm::Byte, None),
+        0x6B | 0x80 | 0x83 | 0xC0 | 0xC1 | 0xC6 => (true, Imm::Byte, None),
+        0x70..=0x7F => (false, Imm::Byte, Some(Branch::Jcc(op & 0xF))),
+        0xA0..=0xA3 => (false, Imm::Offset, None),
+        0xA9 => (false, Imm::Z, None),
+        0xB8..=0xBF => (false, Imm::V, None),
+        0xC2 | 0xCA => (false, Imm::Word, None),
+        0xC3 | 0xC9 | 0xCB | 0xCC | 0xCF | 0xD7 | 0xEC..=0xEF | 0xF1 | 0xF4 | 0xF5 => {
+            (false, Imm::None, None)
+        }
+        0xF8..=0xFD => (false, Imm::None, None),
+        0xC8 => (false, Imm::Enter, None),
+        0xE0..=0xE3 => (false, Imm::Byte, Some(Branch::Loop)),
+        0xE8 => (false, Imm::Z, Some(Branch::Call)),
+        0xE9 => (false, Imm::Z, Some(Branch::Jmp)),
+        0xEB => (false, Imm::Byte, Some(Branch::Jmp)),
+        // `test r/m, imm` is the only member of these groups with an immediate.
+        0xF6 => (true, group3_imm(code.get(i)?, Imm::Byte), None),
+        0xF7 => (true, group3_imm(code.get(i)?, Imm::Z), None),
+        _ => return None,
+    };
+
+    let mut disp = None;
+    let mut rip_relative = false;
+    if modrm {
+        let m = *code.get(i)?;
+        i += 1;
+
+        let (mode, rm) = (m >> 6, m & 7);
+        let mut disp_size = match mode {
+            1 => 1,
+            2 => 4,
+            _ => 0,
+        };
+
+        if mode != 3 && rm == 4 {
+            let sib = *code.get(i)?;
+            i += 1;
+            if mode == 0 && sib & 7 == 5 {
+                disp_size = 4;
+            }
+        } else if mode == 0 && rm == 5 {
+            disp_size = 4;
+            rip_relative = true;
+        }
+
+        if disp_size != 0 {
+            disp = Some((i, disp_size));
+            i += disp_size;
+        }
+    }
+
+    let imm_size = match imm {
+        Imm::None => 0,
+        Imm::Byte => 1,
+        Imm::Word => 2,
+        Imm::Enter => 3,
+        // Operand size prefix is ignored by near branches in 64 bit mode.
+        Imm::Z if opsize && !rex_w && branch.is_none() => 2,
+        Imm::Z => 4,
+        Imm::V if rex_w => 8,
+        Imm::V if opsize => 2,
+        Imm::V => 4,
+        Imm::Offset if adsize => 4,
+        Imm::Offset => 8,
+    };
+    let imm = (imm_size != 0).then_some((i, imm_size));
+    i += imm_size;
+
+    (i <= code.len()).then_some(Instruction {
+        len: i,
+        disp,
+        imm,
+        rip_relative,
+        branch,
+    })
+}
+
+/// Re-encodes whole instructions from `code` located at `from` so they can be executed at `to`.
+/// # Behavior
+/// RIP-relative displacements are adjusted. Relative branches are widened to rel32,
+/// or go through an absolute jump if their destination is out of range.
+/// Branches into `code` go to the relocated copy of their destination.
+/// Returns [`crate::MfError::InvalidInstruction`] if an instruction can't be decoded,
+/// a displacement can't reach its target from `to` or a branch lands inside an instruction.
+#[cfg(all(feature = "internal", target_arch = "x86_64"))]
+pub(crate) fn relocate(code: &[u8], from: usize, to: usize) -> crate::Result<Vec<u8>> {
+    let mut insns = Vec::new();
+    let mut pos = 0;
+    while pos < code.len() {
+        let insn = decode(&code[pos..]).ok_or(crate::MfError::InvalidInstruction(from + pos))?;
+        insns.push((pos, insn));
+        pos += insn.len;
+    }
+
+    let destination = |pos: usize, insn: &Instruction| {
+        let (off, size) = insn.imm.unwrap_or_default();
+        let rel = read_signed(&code[pos + off..pos + off + size]);
+        (from + pos + insn.len).wrapping_add_signed(rel)
+    };
+    let internal = |dest: usize| dest.wrapping_sub(from) < code.len();
+
+    // Branches within `code` always fit in rel32, so the size of every
+    // instruction is known before the destinations are.
+    let mut offsets = Vec::with_capacity(insns.len());
+    let mut size = 0;
+    for (pos, insn) in &insns {
+        offsets.push(size);
+        size += match insn.branch {
+            Some(branch) => {
+                let dest = destination(*pos, insn);
+                let dest = if internal(dest) { to + size } else { dest };
+                encode_branch(branch, &code[*pos..pos + insn.len], to + size, dest).len()
+            }
+            None => insn.len,
+        };
+    }
+
+    let mut out = Vec::with_capacity(size);
+    for (&(pos, insn), &offset) in insns.iter().zip(&offsets) {
+        let at = to + offset;
+        let bytes = &code[pos..pos + insn.len];
+        let invalid = crate::MfError::InvalidInstruction(from + pos);
+
+        if let Some(branch) = insn.branch {
+            let mut dest = destination(pos, &insn);
+            if internal(dest) {
+                let i = insns
+                    .binary_search_by_key(&(dest - from), |(pos, _)| *pos)
+                    .map_err(|_| invalid)?;
+                dest = to + offsets[i];
+            }
+            out.extend(encode_branch(branch, bytes, at, dest));
+        } else if let (true, Some((off, _))) = (insn.rip_relative, insn.disp) {
+            let target =
+                (from + pos + insn.len).wrapping_add_signed(read_signed(&bytes[off..off + 4]));
+            let disp =
+                i32::try_from(target.wrapping_sub(at + insn.len) as isize).map_err(|_| invalid)?;
+            out.extend(&bytes[..off]);
+            out.extend(disp.to_le_bytes());
+            out.extend(&bytes[off + 4..]);
+        } else {
+            out.extend(bytes);
+        }
+    }
+
+    Ok(out)
+}
+
+/// Encodes a branch located at `at` to `dest`, `insn` is the original instruction.
+#[cfg(all(feature = "internal", target_arch = "x86_64"))]
+fn encode_branch(branch: Branch, insn: &[u8], at: usize, dest: usize) -> Vec<u8> {
+    let rel32 = |end: usize| i32::try_from(dest.wrapping_sub(end) as isize).ok();
+    let absolute = |head: &[u8]| [head, &(dest as u64).to_le_bytes()].concat();
+
+    match branch {
+        Branch::Jmp => match rel32(at + 5) {
+            Some(rel) => [&[0xE9][..], &rel.to_le_bytes()].concat(),
+            // jmp [rip]
+            None => absolute(&[0xFF, 0x25, 0, 0, 0, 0]),
+        },
+        Branch::Call => match rel32(at + 5) {
+            Some(rel) => [&[0xE8][..], &rel.to_le_bytes()].concat(),
+            // call [rip + 2]; jmp over the address
+            None => absolute(&[0xFF, 0x15, 2, 0, 0, 0, 0xEB, 8]),
+        },
+        Branch::Jcc(cc) => match rel32(at + 6) {
+            Some(rel) => [&[0x0F, 0x80 | cc][..], &rel.to_le_bytes()].concat(),
+            // Inverted condition skips the absolute jump.
+            None => absolute(&[0x70 | (cc ^ 1), 14, 0xFF, 0x25, 0, 0, 0, 0]),
+        },
+        Branch::Loop => {
+            // Taken branch skips the short jump over the jump to the destination.
+            let head = [&insn[..insn.len() - 1], &[2]].concat();
+            match rel32(at + head.len() + 7) {
+                Some(rel) => [&head[..], &[0xEB, 5, 0xE9], &rel.to_le_bytes()].concat(),
+                None => [&head[..], &absolute(&[0xEB, 14, 0xFF, 0x25, 0, 0, 0, 0])].concat(),
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "internal", target_arch = "x86_64"))]
+fn read_signed(bytes: &[u8]) -> isize {
+    match *bytes {
+        [b] => b as i8 as isize,
+        [a, b] => i16::from_le_bytes([a, b]) as isize,
+        [a, b, c, d] => i32::from_le_bytes([a, b, c, d]) as isize,
+        _ => 0,
+    }
+}
+
+/// Immediate operand of two byte opcodes that have a ModRM byte.
+fn two_byte_imm(op: u8) -> Imm {
+    match op {
+        0x70..=0x73 | 0xA4 | 0xAC | 0xBA | 0xC2 | 0xC4..=0xC6 => Imm::Byte,
+        _ => Imm::None,
+    }
+}
+
+fn group3_imm(modrm: &u8, imm: Imm) -> Imm {
+    match modrm >> 3 & 7 {
+        0 | 1 => imm,
+        _ => Imm::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, Branch};
+
+    #[test]
+    fn test_lengths() {
+        let cases: &[&[u8]] = &[
+            &[0x55],                                           // push rbp
+            &[0x48, 0x89, 0xE5],                               // mov rbp, rsp
+            &[0x48, 0x83, 0xEC, 0x20],                         // sub rsp, 0x20
+            &[0x48, 0x81, 0xEC, 0x00, 0x01, 0x00, 0x00],       // sub rsp, 0x100
+            &[0x48, 0x8B, 0x44, 0x24, 0x08],                   // mov rax, [rsp + 8]
+            &[0x48, 0x8B, 0x84, 0x24, 0x00, 0x01, 0x00, 0x00], // mov rax, [rsp + 0x100]
+            &[0x8B, 0x04, 0x25, 0x00, 0x10, 0x40, 0x00],       // mov eax, [0x401000]
+            &[0x66, 0xC7, 0x00, 0x34, 0x12],                   // mov word [rax], 0x1234
+            &[0x48, 0xB8, 1, 2, 3, 4, 5, 6, 7, 8],             // mov rax, imm64
+            &[0xB8, 1, 2, 3, 4],                               // mov eax, imm32
+            &[0xF6, 0x00, 0x01],                               // test byte [rax], 1
+            &[0xF7, 0xD8],                                     // neg eax
+            &[0xF3, 0x0F, 0x1E, 0xFA],                         // endbr64
+            &[0x0F, 0x1F, 0x44, 0x00, 0x00],                   // nop dword [rax + rax]
+            &[0x66, 0x0F, 0x3A, 0x0F, 0xC1, 0x08],             // palignr xmm0, xmm1, 8
+            &[0xC5, 0xF8, 0x77],                               // vzeroupper
+            &[0xC5, 0xFA, 0x10, 0x45, 0xFC],                   // vmovss xmm0, [rbp - 4]
+            &[0xC4, 0xE3, 0x79, 0x04, 0xC0, 0x01],             // vpermilps xmm0, xmm0, 1
+            &[0x62, 0xF1, 0x7C, 0x48, 0x10, 0x00],             // vmovups zmm0, [rax]
+            &[0xC8, 0x10, 0x00, 0x00],                         // enter 0x10, 0
+            &[0xA1, 1, 2, 3, 4, 5, 6, 7, 8],                   // mov eax, [moffs64]
+            &[0xC3],                                           // ret
+        ];
+
+        for code in cases {
+            assert_eq!(decode(code).map(|i| i.len), Some(code.len()), "{code:02X?}");
+        }
+
+        assert_eq!(decode(&[0x06]), None);
+        assert_eq!(decode(&[0x48, 0x8B]), None);
+    }
+
+    #[test]
+    fn test_relative() {
+        // lea rcx, [rip + 0x100]
+        let lea = decode(&[0x48, 0x8D, 0x0D, 0x00, 0x01, 0x00, 0x00]).unwrap();
+        assert!(lea.rip_relative);
+        assert_eq!(lea.disp, Some((3, 4)));
+
+        let call = [0xE8, 0xFB, 0xFF, 0xFF, 0xFF];
+        let insn = decode(&call).unwrap();
+        assert_eq!(insn.branch, Some(Branch::Call));
+        assert_eq!(insn.imm, Some((1, 4)));
+
+        let jcc = [0x0F, 0x85, 0x10, 0x00, 0x00, 0x00];
+        assert_eq!(decode(&jcc).unwrap().branch, Some(Branch::Jcc(5)));
+        let loopne = [0xE0, 0xFE];
+        assert_eq!(decode(&loopne).unwrap().branch, Some(Branch::Loop));
+    }
+
+    #[test]
+    #[cfg(all(feature = "internal", target_arch = "x86_64"))]
+    fn test_relocate() {
+        use super::relocate;
+        let (from, near, far) = (0x1000_0000, 0x1000_1000, 0x7FFF_0000_0000);
+
+        // lea rcx, [rip + 0x100]
+        let lea = [0x48, 0x8D, 0x0D, 0x00, 0x01, 0x00, 0x00];
+        assert_eq!(
+            relocate(&lea, from, near).unwrap(),
+            [0x48, 0x8D, 0x0D, 0x00, 0xF1, 0xFF, 0xFF]
+        );
+        assert!(relocate(&lea, from, far).is_err());
+
+        // jz +0x10; call -0x100
+        let code = [0x74, 0x10, 0xE8, 0x00, 0xFF, 0xFF, 0xFF];
+        assert_eq!(
+            relocate(&code, from, near).unwrap(),
+            [0x0F, 0x84, 0x0C, 0xF0, 0xFF, 0xFF, 0xE8, 0xFC, 0xEE, 0xFF, 0xFF]
+        );
+        let far_jz = [
+            &[0x75, 0x0E, 0xFF, 0x25, 0, 0, 0, 0][..],
+            &0x1000_0012_u64.to_le_bytes(),
+        ];
+        let far_call = [
+            &[0xFF, 0x15, 2, 0, 0, 0, 0xEB, 8][..],
+            &0x0FFF_FF07_u64.to_le_bytes(),
+        ];
+        assert_eq!(
+            relocate(&code, from, far).unwrap(),
+            [far_jz.concat(), far_call.concat()].concat()
+        );
+
+        // jmp +2 into the code; nop; nop; loop -4
+        let code = [0xEB, 0x02, 0x90, 0x90, 0xE2, 0xFC];
+        assert_eq!(
+            relocate(&code, from, near).unwrap(),
+            [0xE9, 2, 0, 0, 0, 0x90, 0x90, 0xE2, 2, 0xEB, 5, 0xE9, 0xF5, 0xFF, 0xFF, 0xFF]
+        );
+
+        // jmp into the middle of an instruction
+        assert!(relocate(&[0xEB, 0x01, 0xB0, 0x01], from, near).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+#![cfg(unix)]
+mod common;
+
+use common::page_size;
+use core::ptr::NonNull;
+use memflex::{
+    ida_pat,
+    internal::{
+        allocate, free, protect, try_find_pattern, try_read, try_resolve_multilevel,
+        try_terminated_array,
+    },
+    types::{Protection, TStr},
+    MfError,
+};
+
+/// Allocates 3 pages, the second one can't be read.
+fn guarded() -> usize {
+    let start = allocate(None, page_size() * 3, Protection::RW).unwrap() as usize;
+    protect(start + page_size(), page_size(), Protection::empty()).unwrap();
+    start
+}
+
+fn release(start: usize) {
+    protect(start + page_size(), page_size(), Protection::RW).unwrap();
+    free(start, page_size() * 3).unwrap();
+}
+
+#[test]
+fn test_try_read() {
+    let start = guarded();
+    unsafe { ((start + page_size() - 8) as *mut u64).write(0x1337) };
+
+    assert_eq!(
+        unsafe { try_read::<u64>(start + page_size() - 8) }.unwrap(),
+        0x1337
+    );
+    assert!(matches!(
+        unsafe { try_read::<u64>(start + page_size() - 4) },
+        Err(MfError::InvalidAddress(a)) if a == start + page_size() - 4
+    ));
+    assert!(matches!(
+        unsafe { try_read::<u8>(start + page_size()) },
+        Err(MfError::InvalidAddress(_))
+    ));
+
+    release(start);
+}
+
+#[test]
+fn test_try_resolve_multilevel() {
+    let start = guarded();
+    let value = Box::new(1337_i32);
+    unsafe {
+        ((start + 0x10) as *mut usize).write(start + 0x100);
+        ((start + 0x108) as *mut usize).write(&*value as *const i32 as usize);
+        ((start + 0x20) as *mut usize).write(start + page_size());
+    }
+
+    let v = try_resolve_multilevel::<i32>(start as _, &[0x10, 0x8, 0x0]).unwrap();
+    assert_eq!(unsafe { *v }, 1337);
+    assert_eq!(
+        try_resolve_multilevel::<u8>(start as _, &[0x10]).unwrap() as usize,
+        start + 0x10
+    );
+
+    assert!(matches!(
+        try_resolve_multilevel::<i32>(start as _, &[0x20, 0x8, 0x0]),
+        Err(MfError::InvalidPointer { level: 1, address }) if address == start + page_size() + 8
+    ));
+
+    release(start);
+}
+
+#[test]
+fn test_try_terminated_array() {
+    let start = guarded();
+    let text = b"stale pointer";
+    let end = start + page_size() - text.len();
+    unsafe {
+        core::ptr::copy_nonoverlapping(text.as_ptr(), end as *mut u8, text.len());
+        (start as *mut [u8; 6]).write(*b"valid\0");
+    }
+
+    unsafe {
+        assert_eq!(
+            try_terminated_array(start as *const u8, 0, 16).unwrap(),
+            b"valid"
+        );
+        assert!(matches!(
+            try_terminated_array(end as *const u8, 0, 64),
+            Err(MfError::InvalidAddress(a)) if a == start + page_size()
+        ));
+        assert!(matches!(
+            try_terminated_array(end as *const u8, 0, 4),
+            Err(MfError::TerminatorNotFound)
+        ));
+
+        // Items straddling the unreadable page
+        assert!(matches!(
+            try_terminated_array((start + page_size() - 4) as *const u64, 0, 4),
+            Err(MfError::InvalidAddress(_))
+        ));
+
+        let valid = TStr::from_ptr(NonNull::new(start as *mut i8).unwrap());
+        assert_eq!(valid.try_as_str(16).unwrap(), "valid");
+        let stale = TStr::from_ptr(NonNull::new(end as *mut i8).unwrap());
+        assert!(stale.try_as_str(64).is_err());
+
+        (start as *mut [u8; 3]).write([0xFF, 0xFE, 0]);
+        assert!(matches!(valid.try_as_str(16), Err(MfError::InvalidString)));
+    }
+
+    release(start);
+}
+
+#[test]
+fn test_try_find_pattern() {
+    let start = allocate(None, page_size() * 4, Protection::RW).unwrap() as usize;
+    let put = |offset: usize| unsafe {
+        ((start + offset) as *mut [u8; 4]).write([0x13, 0x37, 0xC0, 0xDE]);
+    };
+    put(0x10);
+    put(page_size() + 0x20);
+    put(page_size() * 3 - 2);
+    put(page_size() * 3 + 0x30);
+    protect(start + page_size(), page_size(), Protection::empty()).unwrap();
+    // Splits the mapping, matches can still span both parts
+    protect(start + page_size() * 3, page_size(), Protection::R).unwrap();
+
+    let found = unsafe { try_find_pattern(ida_pat!("13 37 C0 DE"), start as _, page_size() * 4) }
+        .unwrap()
+        .map(|p| p as usize - start)
+        .collect::<Vec<_>>();
+    assert_eq!(found, [0x10, page_size() * 3 - 2, page_size() * 3 + 0x30]);
+
+    let found = unsafe {
+        try_find_pattern(
+            ida_pat!("13 37 C0 DE"),
+            (start + 0x20) as _,
+            page_size() * 3,
+        )
+    }
+    .unwrap()
+    .map(|p| p as usize - start)
+    .collect::<Vec<_>>();
+    assert_eq!(found, [page_size() * 3 - 2]);
+
+    protect(start, page_size() * 4, Protection::RW).unwrap();
+    free(start, page_size() * 4).unwrap();
+}
@@ -0,0 +1,37 @@
+/// Defines `$local` running `$check` against `CurrentProcess` and, with the `external`
+/// feature, `$remote` running it against the current process opened by its id.
+#[allow(unused_macros)]
+macro_rules! source_tests {
+    ($local:ident, $remote:ident: $check:ident) => {
+        #[test]
+        fn $local() {
+            $check(&memflex::internal::CurrentProcess);
+        }
+
+        #[cfg(feature = "external")]
+        #[test]
+        fn $remote() {
+            $check(&memflex::external::find_process_by_id(std::process::id()).unwrap());
+        }
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use source_tests;
+
+/// Size of a memory page of the system.
+#[allow(dead_code)]
+pub fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Name of the module of the C library, the one that contains `getpid`.
+/// Statically linked C libraries are a part of the test executable.
+#[allow(dead_code)]
+pub fn libc_name() -> String {
+    let getpid = unsafe { libc::dlsym(libc::RTLD_DEFAULT, c"getpid".as_ptr()) } as usize;
+    memflex::internal::modules()
+        .find(|m| (m.base as usize..m.base as usize + m.size).contains(&getpid))
+        .unwrap()
+        .name
+}
@@ -0,0 +1,235 @@
+#![cfg(all(unix, feature = "external"))]
+mod common;
+
+use common::page_size;
+use memflex::{
+    external::find_process_by_id,
+    ida_pat,
+    internal::{allocate, free, protect},
+    types::Protection,
+    PatternSet, ScanOptions, YaraPattern,
+};
+
+const CHUNK: usize = 0x100000;
+
+#[test]
+fn test_remote_find_pattern() {
+    let process = find_process_by_id(std::process::id()).unwrap();
+
+    let len = CHUNK * 3;
+    let region = allocate(None, len, Protection::RW).unwrap();
+    let start = region as usize;
+    let memory = unsafe { std::slice::from_raw_parts_mut(region, len) };
+
+    // Straddles a chunk boundary
+    let boundary = (start / CHUNK + 1) * CHUNK;
+    let first = boundary - 2;
+    memory[first - start..][..4].copy_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+
+    // Lies behind an unreadable page
+    let second = boundary + page_size() * 2 + 0x10;
+    memory[second - start..][..4].copy_from_slice(&[0xDE, 0xAD, 0x00, 0xEF]);
+    protect(boundary + page_size(), page_size(), Protection::empty()).unwrap();
+
+    let found = process
+        .find_pattern(ida_pat!("DE AD ? EF"), start, len)
+        .collect::<Vec<_>>();
+    assert_eq!(found, [first, second]);
+
+    free(start, len).unwrap();
+}
+
+#[test]
+fn test_remote_find_pattern_set() {
+    let process = find_process_by_id(std::process::id()).unwrap();
+
+    let len = CHUNK * 2;
+    let region = allocate(None, len, Protection::RW).unwrap();
+    let start = region as usize;
+    let memory = unsafe { std::slice::from_raw_parts_mut(region, len) };
+
+    let mut set = PatternSet::new();
+    let long = set.add(ida_pat!("DE AD ? EF 01 02 03 04"));
+    let short = set.add(ida_pat!("BE EF"));
+
+    // Long one straddles a chunk boundary, short one lies in the overlap of the two chunks.
+    let boundary = (start / CHUNK + 1) * CHUNK;
+    let first = boundary - 6;
+    memory[first - start..][..8].copy_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF, 1, 2, 3, 4]);
+    let second = boundary + 0x10;
+    memory[second - start..][..8].copy_from_slice(&[0xDE, 0xAD, 0x00, 0xEF, 1, 2, 3, 4]);
+
+    let mut found = process
+        .find_pattern_set(&set, start, len)
+        .map(|(id, m)| (id, m.address()))
+        .collect::<Vec<_>>();
+    found.sort();
+    assert_eq!(found, [(long, first), (long, second), (short, first + 2)]);
+
+    free(start, len).unwrap();
+}
+
+#[test]
+fn test_remote_variable_length() {
+    let process = find_process_by_id(std::process::id()).unwrap();
+
+    let len = CHUNK * 2;
+    let region = allocate(None, len, Protection::RW).unwrap();
+    let start = region as usize;
+    let memory = unsafe { std::slice::from_raw_parts_mut(region, len) };
+
+    let pat = YaraPattern::from_yara_style("DE AD ( 01 | 02 [0-16] 03 )").unwrap();
+
+    // Long alternative straddles a chunk boundary
+    let boundary = (start / CHUNK + 1) * CHUNK;
+    let first = boundary - 4;
+    memory[first - start..][..8].copy_from_slice(&[0xDE, 0xAD, 0x02, 0, 0, 0, 0, 0x03]);
+
+    // Short one lies right before an unreadable page
+    let gap = boundary + page_size() * 4;
+    let second = gap - 3;
+    memory[second - start..][..3].copy_from_slice(&[0xDE, 0xAD, 0x01]);
+    protect(gap, page_size(), Protection::empty()).unwrap();
+
+    // and at the very end of the range
+    let third = start + len - 3;
+    memory[third - start..].copy_from_slice(&[0xDE, 0xAD, 0x01]);
+
+    let found = process.find_pattern(&pat, start, len).collect::<Vec<_>>();
+    assert_eq!(found, [first, second, third]);
+
+    let mut set = PatternSet::new();
+    set.add(pat);
+    set.add(ida_pat!("AD 01"));
+    let mut found = process
+        .find_pattern_set(&set, start, len)
+        .map(|(id, m)| (id, m.address()))
+        .collect::<Vec<_>>();
+    found.sort();
+    assert_eq!(
+        found,
+        [
+            (0, first),
+            (0, second),
+            (0, third),
+            (1, second + 1),
+            (1, third + 1)
+        ]
+    );
+
+    protect(gap, page_size(), Protection::RW).unwrap();
+    free(start, len).unwrap();
+}
+
+#[test]
+fn test_remote_scan_options() {
+    let process = find_process_by_id(std::process::id()).unwrap();
+
+    let len = CHUNK * 3;
+    let region = allocate(None, len, Protection::RW).unwrap();
+    let start = region as usize;
+    let memory = unsafe { std::slice::from_raw_parts_mut(region, len) };
+
+    let pat = YaraPattern::from_yara_style("DE AD ( 01 | 02 [0-16] 03 )").unwrap();
+    let mut put = |address: usize, bytes: &[u8]| {
+        memory[address - start..][..bytes.len()].copy_from_slice(bytes);
+        address
+    };
+
+    let boundary = (start / CHUNK + 1) * CHUNK;
+    let expected = [
+        put(start, &[0xDE, 0xAD, 0x01]),
+        // Straddles a chunk boundary
+        put(boundary - 4, &[0xDE, 0xAD, 0x02, 0, 0, 0, 0, 0x03]),
+        put(boundary + 0x10, &[0xDE, 0xAD, 0x01]),
+        // Right before and after an unreadable page
+        put(boundary + page_size() * 4 - 3, &[0xDE, 0xAD, 0x01]),
+        put(boundary + page_size() * 5, &[0xDE, 0xAD, 0x01]),
+        put(boundary + CHUNK - 1, &[0xDE, 0xAD, 0x01]),
+        put(start + len - 3, &[0xDE, 0xAD, 0x01]),
+    ];
+    protect(boundary + page_size() * 4, page_size(), Protection::empty()).unwrap();
+
+    let reverse = ScanOptions {
+        reverse: true,
+        ..Default::default()
+    };
+    let found = process
+        .find_pattern_with(&pat, start, len, reverse)
+        .collect::<Vec<_>>();
+    assert_eq!(found, expected.iter().rev().copied().collect::<Vec<_>>());
+
+    // Range ends right after the short match, in the middle of the long alternative
+    let end = boundary + CHUNK + 2;
+    let found = process
+        .find_pattern_with(&pat, boundary - 0x10, end - (boundary - 0x10), reverse)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        expected[1..6].iter().rev().copied().collect::<Vec<_>>()
+    );
+
+    // Closest match before an address
+    let options = ScanOptions {
+        max_results: Some(1),
+        ..reverse
+    };
+    let found = process
+        .find_pattern_with(&pat, start, boundary + page_size() * 5 - start, options)
+        .collect::<Vec<_>>();
+    assert_eq!(found, [boundary + page_size() * 4 - 3]);
+
+    for align in [2, 16] {
+        let options = ScanOptions {
+            align,
+            ..Default::default()
+        };
+        let aligned = expected
+            .iter()
+            .copied()
+            .filter(|a| a.is_multiple_of(align))
+            .collect::<Vec<_>>();
+        assert!(!aligned.is_empty());
+
+        let found = process
+            .find_pattern_with(&pat, start, len, options)
+            .collect::<Vec<_>>();
+        assert_eq!(found, aligned);
+
+        let found = process
+            .find_pattern_with(
+                &pat,
+                start,
+                len,
+                ScanOptions {
+                    reverse: true,
+                    ..options
+                },
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(found, aligned.iter().rev().copied().collect::<Vec<_>>());
+    }
+
+    protect(boundary + page_size() * 4, page_size(), Protection::RW).unwrap();
+    free(start, len).unwrap();
+}
+
+#[test]
+fn test_remote_captures() {
+    static CODE: [u8; 11] = [
+        0x4D, 0x46, 0x4C, 0x58, 0x48, 0x8D, 0x0D, 0x00, 0x01, 0x00, 0x00,
+    ];
+
+    let process = find_process_by_id(std::process::id()).unwrap();
+    let exe = process.name().unwrap();
+    let start = CODE.as_ptr() as usize;
+
+    let m = process
+        .find_matches_in_module(ida_pat!("4D 46 4C 58 48 8D 0D & ? ? 00 00"), &exe)
+        .unwrap()
+        .find(|m| m.address() == start)
+        .unwrap();
+
+    assert_eq!(process.read_capture::<u32>(&m, 0).unwrap(), 0x100);
+    assert_eq!(process.rip_relative(&m, 0, 11).unwrap(), start + 11 + 0x100);
+}
@@ -0,0 +1,121 @@
+#![cfg(unix)]
+mod common;
+
+use common::page_size;
+use memflex::{
+    internal::{allocate, free, protect, CurrentProcess, ProtectionGuard},
+    types::{MemoryRegion, Protection},
+    MemorySink, MemorySource, MfError,
+};
+
+/// Protection of every page in the range.
+fn pages(start: usize, count: usize) -> Vec<Protection> {
+    let maps = CurrentProcess.maps().unwrap();
+    (0..count)
+        .map(|i| {
+            let page = start + i * page_size();
+            maps.iter()
+                .find(|r| (r.from..r.to).contains(&page))
+                .unwrap()
+                .prot
+        })
+        .collect()
+}
+
+#[test]
+fn test_protection_guard() {
+    let start = allocate(None, page_size() * 4, Protection::RW).unwrap() as usize;
+    protect(start + page_size(), page_size(), Protection::R).unwrap();
+    protect(start + page_size() * 2, page_size(), Protection::RX).unwrap();
+    let before = [
+        Protection::RW,
+        Protection::R,
+        Protection::RX,
+        Protection::RW,
+    ];
+    assert_eq!(pages(start, 4), before);
+
+    {
+        // Unaligned range spanning three mappings
+        let guard = ProtectionGuard::new(
+            start + page_size() - 2,
+            page_size() * 2 + 1,
+            Protection::RWX,
+        )
+        .unwrap();
+        assert_eq!(
+            guard.original(),
+            [
+                MemoryRegion {
+                    from: start,
+                    to: start + page_size(),
+                    prot: Protection::RW
+                },
+                MemoryRegion {
+                    from: start + page_size(),
+                    to: start + page_size() * 2,
+                    prot: Protection::R
+                },
+                MemoryRegion {
+                    from: start + page_size() * 2,
+                    to: start + page_size() * 3,
+                    prot: Protection::RX
+                },
+            ]
+        );
+        assert_eq!(
+            pages(start, 4),
+            [
+                Protection::RWX,
+                Protection::RWX,
+                Protection::RWX,
+                Protection::RW
+            ]
+        );
+        unsafe { ((start + page_size() * 2 + 8) as *mut u64).write(0x1337) };
+    }
+    assert_eq!(pages(start, 4), before);
+
+    let guard = ProtectionGuard::new(start + page_size(), 1, Protection::empty()).unwrap();
+    assert_eq!(pages(start + page_size(), 1), [Protection::empty()]);
+    guard.restore().unwrap();
+    assert_eq!(pages(start, 4), before);
+
+    protect(start, page_size() * 4, Protection::RW).unwrap();
+    free(start, page_size() * 4).unwrap();
+}
+
+#[test]
+fn test_writable_guard() {
+    let start = allocate(None, page_size() * 2, Protection::R).unwrap() as usize;
+    protect(start + page_size(), page_size(), Protection::RX).unwrap();
+
+    {
+        // Write permission is added to each mapping, data doesn't become executable
+        let _guard = ProtectionGuard::writable(start + page_size() - 1, 2).unwrap();
+        assert_eq!(pages(start, 2), [Protection::RW, Protection::RWX]);
+    }
+    assert_eq!(pages(start, 2), [Protection::R, Protection::RX]);
+
+    CurrentProcess.write_buf(start + 8, &[1, 2]).unwrap();
+    assert_eq!(unsafe { ((start + 8) as *const [u8; 2]).read() }, [1, 2]);
+    assert_eq!(pages(start, 2), [Protection::R, Protection::RX]);
+
+    free(start, page_size() * 2).unwrap();
+}
+
+#[test]
+fn test_protection_guard_unmapped() {
+    let start = allocate(None, page_size() * 3, Protection::RW).unwrap() as usize;
+    free(start + page_size(), page_size()).unwrap();
+
+    assert!(matches!(
+        ProtectionGuard::new(start, page_size() * 3, Protection::R),
+        Err(MfError::InvalidAddress(a)) if a == start + page_size()
+    ));
+    // Nothing was changed
+    assert_eq!(pages(start, 1), [Protection::RW]);
+    assert_eq!(pages(start + page_size() * 2, 1), [Protection::RW]);
+
+    free(start, page_size() * 3).unwrap();
+}
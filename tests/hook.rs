@@ -0,0 +1,260 @@
+#![cfg(all(unix, target_arch = "x86_64"))]
+mod common;
+
+use common::page_size;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use memflex::{
+    internal::{allocate, free, protect, InlineHook, MidHook},
+    types::Protection,
+    MfError, ResolveBy,
+};
+use std::{hint::black_box, sync::Arc};
+
+/// Loads a trampoline stored by a test, every test that calls one has its own static.
+fn original(trampoline: &AtomicUsize) -> extern "C" fn(i32) -> i32 {
+    unsafe { std::mem::transmute(trampoline.load(Ordering::SeqCst)) }
+}
+
+#[inline(never)]
+extern "C" fn square(x: i32) -> i32 {
+    let mut total = 0;
+    for _ in 0..black_box(x) {
+        total += x;
+    }
+    total
+}
+
+static SQUARE: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn add_one(x: i32) -> i32 {
+    original(&SQUARE)(x) + 1
+}
+
+#[test]
+fn test_hook_function() {
+    let mut hook = unsafe { InlineHook::new(square as *const u8, add_one as *const u8) }.unwrap();
+    assert!(!hook.is_enabled());
+    assert_eq!(hook.target(), square as *const u8);
+    SQUARE.store(hook.trampoline() as usize, Ordering::SeqCst);
+
+    let square = black_box(square as extern "C" fn(i32) -> i32);
+    assert_eq!(square(5), 25);
+
+    unsafe { hook.enable() }.unwrap();
+    assert!(hook.is_enabled());
+    assert_eq!(square(5), 26);
+    assert_eq!(original(&SQUARE)(5), 25);
+
+    unsafe { hook.disable() }.unwrap();
+    assert_eq!(square(5), 25);
+    unsafe { hook.enable() }.unwrap();
+    assert_eq!(square(4), 17);
+
+    unsafe { hook.unhook() }.unwrap();
+    assert_eq!(square(5), 25);
+}
+
+/// Writes the code and data at offset 0x800 to a new page and makes it executable.
+fn code(bytes: &[u8], data: &[u8]) -> usize {
+    let page = allocate(None, page_size(), Protection::RW).unwrap() as usize;
+    unsafe {
+        (page as *mut u8).copy_from_nonoverlapping(bytes.as_ptr(), bytes.len());
+        ((page + 0x800) as *mut u8).copy_from_nonoverlapping(data.as_ptr(), data.len());
+    }
+    protect(page, page_size(), Protection::RX).unwrap();
+    page
+}
+
+static RELOCATED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn add_hundred(x: i32) -> i32 {
+    original(&RELOCATED)(x) + 100
+}
+
+#[test]
+fn test_hook_relocation() {
+    #[rustfmt::skip]
+    let page = code(&[
+        0x85, 0xFF,                         // test edi, edi
+        0x74, 0x07,                         // jz +7
+        0x8B, 0x05, 0xF6, 0x07, 0, 0,       // mov eax, [rip + 0x7F6]
+        0xC3,                               // ret
+        0xB8, 42, 0, 0, 0,                  // mov eax, 42
+        0xC3,                               // ret
+    ], &[7, 0, 0, 0]);
+    let f: extern "C" fn(i32) -> i32 = unsafe { std::mem::transmute(page) };
+    assert_eq!((f(1), f(0)), (7, 42));
+
+    let mut hook = unsafe { InlineHook::new(page as _, add_hundred as *const u8) }.unwrap();
+    let trampoline: extern "C" fn(i32) -> i32 = unsafe { std::mem::transmute(hook.trampoline()) };
+    RELOCATED.store(trampoline as usize, Ordering::SeqCst);
+    assert!((hook.trampoline() as usize).abs_diff(page) < 0x8000_0000);
+
+    unsafe { hook.enable() }.unwrap();
+    // Relocated jz and RIP-relative load behave like the original ones
+    assert_eq!((f(1), f(0)), (107, 142));
+    assert_eq!((trampoline(1), trampoline(0)), (7, 42));
+    drop(hook);
+    assert_eq!((f(1), f(0)), (7, 42));
+
+    free(page, page_size()).unwrap();
+}
+
+#[test]
+fn test_hook_errors() {
+    // ret followed by padding can be overwritten
+    let page = code(&[0x31, 0xC0, 0xC3, 0xCC, 0xCC, 0xCC], &[]);
+    let hook = unsafe { InlineHook::new(page as _, add_one as *const u8) }.unwrap();
+    drop(hook);
+    free(page, page_size()).unwrap();
+
+    // but not followed by another function
+    let page = code(&[0x31, 0xC0, 0xC3, 0xB8, 1, 0, 0, 0, 0xC3], &[]);
+    assert!(matches!(
+        unsafe { InlineHook::new(page as _, add_one as *const u8) },
+        Err(MfError::InvalidInstruction(a)) if a == page + 3
+    ));
+    free(page, page_size()).unwrap();
+
+    let page = code(&[0x90, 0x06, 0x90, 0x90, 0x90], &[]);
+    assert!(matches!(
+        unsafe { InlineHook::new(page as _, add_one as *const u8) },
+        Err(MfError::InvalidInstruction(a)) if a == page + 1
+    ));
+    free(page, page_size()).unwrap();
+
+    // Code changed after the hook was created
+    let page = code(&[0x31, 0xC0, 0xC3, 0xCC, 0xCC, 0xCC], &[]);
+    let mut hook = unsafe { InlineHook::new(page as _, add_one as *const u8) }.unwrap();
+    protect(page, page_size(), Protection::RWX).unwrap();
+    unsafe { (page as *mut u8).write(0x33) };
+    assert!(matches!(
+        unsafe { hook.enable() },
+        Err(MfError::UnexpectedBytes(a)) if a == page
+    ));
+    drop(hook);
+    free(page, page_size()).unwrap();
+}
+
+#[test]
+fn test_mid_hook() {
+    #[rustfmt::skip]
+    let page = code(&[
+        0x48, 0x89, 0xF8,                   // mov rax, rdi
+        0x48, 0x01, 0xF0,                   // add rax, rsi
+        0x48, 0x83, 0xC0, 0x01,             // add rax, 1
+        0xC3,                               // ret
+    ], &[]);
+    let f: extern "C" fn(u64, u64) -> u64 = unsafe { std::mem::transmute(page) };
+    assert_eq!(f(2, 3), 6);
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let mut hook = unsafe {
+        MidHook::new((page + 3) as _, move |ctx| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            assert_eq!((ctx.rax, ctx.rdi), (ctx.rdi, 2));
+            ctx.rsi = 100;
+        })
+    }
+    .unwrap();
+    assert_eq!(hook.address() as usize, page + 3);
+
+    unsafe { hook.enable() }.unwrap();
+    assert_eq!(f(2, 3), 103);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    unsafe { hook.disable() }.unwrap();
+    assert_eq!(f(2, 3), 6);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    unsafe { hook.unhook() }.unwrap();
+    assert_eq!(Arc::strong_count(&calls), 1);
+    free(page, page_size()).unwrap();
+}
+
+#[test]
+fn test_mid_hook_state() {
+    #[rustfmt::skip]
+    let page = code(&[
+        0x48, 0x89, 0x7C, 0x24, 0xF8,       // mov [rsp - 8], rdi
+        0x66, 0x48, 0x0F, 0x6E, 0xC6,       // movq xmm0, rsi
+        0x48, 0x39, 0xF7,                   // cmp rdi, rsi
+        0x90, 0x90, 0x90, 0x90, 0x90,       // nop
+        0x0F, 0x9C, 0xC0,                   // setl al
+        0x0F, 0xB6, 0xC0,                   // movzx eax, al
+        0x48, 0x03, 0x44, 0x24, 0xF8,       // add rax, [rsp - 8]
+        0x66, 0x48, 0x0F, 0x7E, 0xC1,       // movq rcx, xmm0
+        0x48, 0x01, 0xC8,                   // add rax, rcx
+        0xC3,                               // ret
+    ], &[]);
+    let f: extern "C" fn(u64, u64) -> u64 = unsafe { std::mem::transmute(page) };
+    assert_eq!((f(10, 20), f(20, 10)), (31, 30));
+
+    // Flags and the red zone survive the callback
+    let mut hook = unsafe {
+        MidHook::new((page + 13) as _, |ctx| {
+            let saved = ((ctx.rsp - 8) as *const u64).read();
+            assert_eq!(saved, ctx.rdi);
+            assert_eq!(ctx.xmm[0], ctx.rsi as u128);
+        })
+    }
+    .unwrap();
+    unsafe { hook.enable() }.unwrap();
+    assert_eq!((f(10, 20), f(20, 10)), (31, 30));
+    drop(hook);
+
+    let mut hook = unsafe { MidHook::new((page + 13) as _, |ctx| ctx.xmm[0] = 1000) }.unwrap();
+    unsafe { hook.enable() }.unwrap();
+    assert_eq!((f(10, 20), f(20, 10)), (1011, 1020));
+    drop(hook);
+
+    free(page, page_size()).unwrap();
+}
+
+#[inline(never)]
+extern "C" fn mul(a: i32, b: i32) -> i32 {
+    black_box(a) * b
+}
+
+fn resolve_mul<const N: usize>(_: ResolveBy<N>) -> usize {
+    mul as *const () as usize
+}
+
+memflex::function! {
+    extern "C" fn MUL(i32, i32) -> i32 = (resolve_mul)"mul"#0;
+}
+
+extern "C" fn mul_negated(a: i32, b: i32) -> i32 {
+    -MUL.call_original()(a, b)
+}
+
+#[test]
+fn test_function_hook() {
+    assert_eq!(MUL.call_original()(2, 3), 6);
+
+    let mut handle = unsafe { MUL.hook(mul_negated) }.unwrap();
+    assert!(handle.is_enabled());
+    assert_eq!(MUL(2, 3), -6);
+    assert_eq!(handle.original()(2, 3), 6);
+    assert!(matches!(
+        unsafe { MUL.hook(mul_negated) },
+        Err(MfError::PatchOverlap(a)) if a == mul as *const () as usize
+    ));
+
+    unsafe { handle.disable() }.unwrap();
+    assert_eq!(MUL(2, 3), 6);
+    assert_eq!(MUL.call_original()(2, 3), 6);
+    unsafe { handle.enable() }.unwrap();
+    assert_eq!(MUL(2, 4), -8);
+
+    unsafe { handle.unhook() }.unwrap();
+    assert_eq!(MUL(2, 3), 6);
+    assert_eq!(MUL.call_original() as usize, mul as *const () as usize);
+
+    // Can be hooked again after the handle is gone
+    let handle = unsafe { MUL.hook(mul_negated) }.unwrap();
+    assert_eq!(MUL(3, 3), -9);
+    drop(handle);
+    assert_eq!(MUL(3, 3), 9);
+}
@@ -0,0 +1,76 @@
+#![cfg(unix)]
+use core::sync::atomic::{AtomicUsize, Ordering};
+use memflex::{
+    internal::{find_module_by_name, try_read, CurrentProcess, ImportHook},
+    types::ModuleInfo,
+    MemorySource, MfError, Patch,
+};
+
+static ORIGINAL: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn getpid_negated() -> i32 {
+    let original: extern "C" fn() -> i32 =
+        unsafe { std::mem::transmute(ORIGINAL.load(Ordering::SeqCst)) };
+    -original()
+}
+
+fn main_module() -> ModuleInfo {
+    let exe = std::env::current_exe().unwrap();
+    find_module_by_name(exe.file_name().unwrap().to_str().unwrap()).unwrap()
+}
+
+#[test]
+fn test_import_hook() {
+    let module = main_module();
+    let pid = unsafe { libc::getpid() };
+
+    let hook = unsafe { ImportHook::new(&module, "getpid", getpid_negated as *const u8) }.unwrap();
+    ORIGINAL.store(hook.original() as usize, Ordering::SeqCst);
+
+    let getpid = unsafe { libc::dlsym(libc::RTLD_DEFAULT, c"getpid".as_ptr()) };
+    assert_eq!(hook.original(), getpid as *const u8);
+    assert!(hook.slots().count() > 0);
+    for slot in hook.slots() {
+        let range = module.base as usize..module.base as usize + module.size;
+        assert!(range.contains(&slot));
+        assert_eq!(
+            unsafe { *(slot as *const usize) },
+            getpid_negated as *const () as usize
+        );
+    }
+
+    assert_eq!(unsafe { libc::getpid() }, -pid);
+
+    // Reads and writes of the process itself don't depend on getpid
+    let data = Box::new(0x1122_3344_u32);
+    let address = &*data as *const u32 as usize;
+    assert_eq!(unsafe { try_read::<u32>(address) }.unwrap(), 0x1122_3344);
+    assert!(CurrentProcess
+        .maps()
+        .unwrap()
+        .iter()
+        .any(|r| r.from <= address && address < r.to));
+    let patch = Patch::new(address, [0xFF]).apply(&CurrentProcess).unwrap();
+    assert_eq!(unsafe { try_read::<u32>(address) }.unwrap(), 0x1122_33FF);
+    patch.restore().unwrap();
+
+    hook.restore().unwrap();
+    assert_eq!(unsafe { libc::getpid() }, pid);
+}
+
+#[test]
+fn test_import_errors() {
+    assert!(matches!(
+        unsafe { ImportHook::new(&main_module(), "not_imported", std::ptr::null()) },
+        Err(MfError::ImportNotFound)
+    ));
+
+    let module = ModuleInfo {
+        base: 0x1000 as _,
+        size: 0x1000,
+    };
+    assert!(matches!(
+        unsafe { ImportHook::new(&module, "getpid", std::ptr::null()) },
+        Err(MfError::ModuleNotFound)
+    ));
+}
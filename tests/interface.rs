@@ -44,6 +44,7 @@ mod game {
     }
 }
 
+#[repr(C, align(8))]
 struct CFoo([u8; 0x10]);
 
 memflex::interface! {
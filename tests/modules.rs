@@ -0,0 +1,54 @@
+#![cfg(unix)]
+mod common;
+
+use common::libc_name;
+use memflex::{
+    ida_pat,
+    internal::{current_module, find_module_by_name, find_pattern_set_in_module, modules},
+    MemorySource, PatternSet,
+};
+
+#[test]
+fn test_modules() {
+    let exe = std::env::current_exe().unwrap();
+    let exe_name = exe.file_name().unwrap().to_str().unwrap();
+
+    let all = modules().collect::<Vec<_>>();
+    assert_eq!(all[0].name, exe_name);
+
+    let name = libc_name();
+    let libc = find_module_by_name(&name).unwrap();
+    let listed = all.iter().find(|m| m.name == name).unwrap();
+    assert_eq!(listed.base, libc.base);
+    assert_eq!(listed.size, libc.size);
+
+    let source = memflex::internal::CurrentProcess;
+    assert_eq!(source.find_module(&name).unwrap().base, libc.base);
+    assert!(source.find_module(&name.to_uppercase()).is_err());
+}
+
+#[test]
+fn test_current_module() {
+    let exe = std::env::current_exe().unwrap();
+    let module = current_module().unwrap();
+
+    assert_eq!(module.name, exe.file_name().unwrap().to_str().unwrap());
+}
+
+#[test]
+fn test_pattern_set_in_module() {
+    let mut set = PatternSet::new();
+    let elf = set.add(ida_pat!("7F 45 4C 46 02 01 01"));
+    let missing = set.add(ida_pat!("DE AD BE EF DE AD BE EF 13 37"));
+
+    let name = libc_name();
+    let libc = find_module_by_name(&name).unwrap();
+    let found = find_pattern_set_in_module(&set, &name)
+        .unwrap()
+        .collect::<Vec<_>>();
+
+    assert!(found
+        .iter()
+        .any(|(id, m)| *id == elf && m.address() == libc.base as usize));
+    assert!(found.iter().all(|(id, _)| *id != missing));
+}
@@ -0,0 +1,138 @@
+#![cfg(unix)]
+mod common;
+
+use common::page_size;
+use memflex::{
+    internal::{allocate, free, protect, CurrentProcess},
+    types::Protection,
+    MemorySink, MemorySource, MfError, Patch, PatchSet,
+};
+
+fn bytes(address: usize, len: usize) -> Vec<u8> {
+    unsafe { std::slice::from_raw_parts(address as *const u8, len).to_vec() }
+}
+
+#[test]
+fn test_patch_encoding() {
+    assert_eq!(Patch::nop(0x1000, 3).bytes, [0x90; 3]);
+    assert_eq!(Patch::nop(0x1000, 3).range(), 0x1000..0x1003);
+}
+
+#[test]
+#[cfg(target_arch = "x86_64")]
+fn test_jump_encoding() {
+    assert_eq!(Patch::jump(0x1000, 0x2000).bytes, [0xE9, 0xFB, 0x0F, 0, 0]);
+    assert_eq!(
+        Patch::jump(0x2000, 0x1000).bytes,
+        [0xE9, 0xFB, 0xEF, 0xFF, 0xFF]
+    );
+    assert_eq!(
+        Patch::jump(0x1000, 0x7FFF_0000_1000).bytes,
+        [0xFF, 0x25, 0, 0, 0, 0, 0x00, 0x10, 0, 0, 0xFF, 0x7F, 0, 0]
+    );
+}
+
+fn check(target: &impl MemorySink) {
+    let start = allocate(None, page_size(), Protection::RW).unwrap() as usize;
+    unsafe { (start as *mut [u8; 8]).write(*b"\x01\x02\x03\x04\x05\x06\x07\x08") };
+    let original = bytes(start, 8);
+
+    let mut patch = Patch::new(start + 1, [0xAA, 0xBB])
+        .expect([0x02, 0x03])
+        .apply(target)
+        .unwrap();
+    assert_eq!(patch.original(), [0x02, 0x03]);
+    assert_eq!(bytes(start, 4), [0x01, 0xAA, 0xBB, 0x04]);
+
+    patch.disable().unwrap();
+    assert!(!patch.is_applied());
+    assert_eq!(bytes(start, 8), original);
+    patch.enable().unwrap();
+    assert_eq!(bytes(start, 4), [0x01, 0xAA, 0xBB, 0x04]);
+    drop(patch);
+    assert_eq!(bytes(start, 8), original);
+
+    assert!(matches!(
+        Patch::nop(start, 2).expect([0x01, 0xFF]).apply(target),
+        Err(MfError::UnexpectedBytes(a)) if a == start
+    ));
+    assert_eq!(bytes(start, 8), original);
+
+    {
+        let mut set = PatchSet::new(target);
+        assert_eq!(set.apply(Patch::nop(start, 2)).unwrap(), 0);
+        assert_eq!(set.apply(Patch::new(start + 4, [0; 4])).unwrap(), 1);
+        assert!(matches!(
+            set.apply(Patch::new(start + 1, [0xCC])),
+            Err(MfError::PatchOverlap(a)) if a == start
+        ));
+        assert!(matches!(
+            set.apply(Patch::new(start + 3, [0xCC; 2])),
+            Err(MfError::PatchOverlap(a)) if a == start + 4
+        ));
+        // Adjacent patches don't overlap
+        set.apply(Patch::new(start + 2, [0xCC, 0xDD])).unwrap();
+        assert_eq!(set.len(), 3);
+        assert_eq!(bytes(start, 8), [0x90, 0x90, 0xCC, 0xDD, 0, 0, 0, 0]);
+    }
+    assert_eq!(bytes(start, 8), original);
+
+    let mut set = PatchSet::new(target);
+    set.apply(Patch::new(start, [0xFF])).unwrap();
+    set.restore().unwrap();
+    assert!(set.is_empty());
+    assert_eq!(bytes(start, 8), original);
+
+    protect(start, page_size(), Protection::empty()).unwrap();
+    assert!(matches!(
+        Patch::new(start, [0]).apply(target),
+        Err(MfError::InvalidAddress(a)) if a == start
+    ));
+
+    protect(start, page_size(), Protection::RW).unwrap();
+    free(start, page_size()).unwrap();
+}
+
+#[test]
+fn test_patch() {
+    check(&CurrentProcess);
+}
+
+#[cfg(feature = "external")]
+#[test]
+fn test_remote_patch() {
+    check(&memflex::external::find_process_by_id(std::process::id()).unwrap());
+}
+
+#[test]
+#[cfg(target_arch = "x86_64")]
+fn test_patch_code() {
+    let code = allocate(None, page_size() * 2, Protection::RW).unwrap() as usize;
+    // mov eax, 1; ret
+    unsafe { (code as *mut [u8; 6]).write([0xB8, 1, 0, 0, 0, 0xC3]) };
+    // mov eax, 2; ret
+    unsafe { ((code + page_size()) as *mut [u8; 6]).write([0xB8, 2, 0, 0, 0, 0xC3]) };
+    protect(code, page_size() * 2, Protection::RX).unwrap();
+
+    let f: extern "C" fn() -> u32 = unsafe { std::mem::transmute(code) };
+    assert_eq!(f(), 1);
+
+    let patch = Patch::new(code + 1, [7]).apply(&CurrentProcess).unwrap();
+    assert_eq!(f(), 7);
+    drop(patch);
+    assert_eq!(f(), 1);
+
+    let jump = Patch::jump(code, code + page_size())
+        .apply(&CurrentProcess)
+        .unwrap();
+    assert_eq!(f(), 2);
+    jump.restore().unwrap();
+    assert_eq!(f(), 1);
+
+    // Protection of the code is kept
+    let maps = CurrentProcess.maps().unwrap();
+    let region = maps.iter().find(|r| r.from <= code && code < r.to).unwrap();
+    assert_eq!(region.prot, Protection::RX);
+
+    free(code, page_size() * 2).unwrap();
+}
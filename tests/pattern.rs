@@ -16,3 +16,215 @@ fn test_pattern_search() {
     assert!(memory.windows(peid.len()).any(|t| peid.matches(t)));
     assert!(memory.windows(code.len()).any(|t| code.matches(t)));
 }
+
+#[test]
+fn test_dyn_pattern_parsing() {
+    use memflex::{DynPattern, MfError};
+
+    let ida = DynPattern::from_ida_style("48 8B ? ?? E8").unwrap();
+    assert_eq!(ida.len(), 5);
+    assert_eq!(ida.to_ida_style(), "48 8B ? ? E8");
+    assert_eq!(ida.to_peid_style(), "48 8B ?? ?? E8");
+
+    let (code, mask) = ida.to_code_style();
+    assert_eq!(DynPattern::from_code_style_str(&code, &mask).unwrap(), ida);
+    assert_eq!(DynPattern::from(ida_pat!("48 8B ? ? E8")), ida);
+
+    assert!(matches!(
+        DynPattern::from_peid_style("48 ? E8"),
+        Err(MfError::InvalidPattern(3))
+    ));
+    assert!(matches!(
+        DynPattern::from_ida_style("48 8B5"),
+        Err(MfError::InvalidPattern(3))
+    ));
+    assert!(matches!(
+        DynPattern::from_code_style(b"\x48\x8B", "x?x"),
+        Err(MfError::InvalidPattern(2))
+    ));
+    assert!(matches!(
+        DynPattern::from_ida_style("  "),
+        Err(MfError::InvalidPattern(0))
+    ));
+}
+
+#[test]
+fn test_yara_pattern_parsing() {
+    use memflex::{MfError, YaraPattern};
+
+    let pat = YaraPattern::from_yara_style("4D5A ?? 9? [2] ( 01 | 02 [0-3] 03 ) ?F").unwrap();
+    assert_eq!((pat.min_len(), pat.len()), (8, 12));
+    assert_eq!(
+        pat.to_yara_style().as_deref(),
+        Some("{ 4D 5A ?? 9? [2] ( 01 | 02 [0-3] 03 ) ?F }")
+    );
+    assert_eq!(
+        YaraPattern::from_yara_style(&pat.to_yara_style().unwrap()).unwrap(),
+        pat
+    );
+
+    assert!(pat.matches(b"\x4D\x5A\x00\x90\xAA\xBB\x01\x0F"));
+    assert!(pat.matches(b"\x4D\x5A\x00\x9F\xAA\xBB\x02\x03\x1F"));
+    assert!(pat.matches(b"\x4D\x5A\x00\x9F\xAA\xBB\x02\xFF\xFF\xFF\x03\x2F"));
+    assert!(!pat.matches(b"\x4D\x5A\x00\x9F\xAA\xBB\x02\xFF\xFF\xFF\xFF\x03\x2F"));
+    assert!(!pat.matches(b"\x4D\x5A\x00\x80\xAA\xBB\x01\x0F"));
+
+    // Fixed prefix is used to anchor the search
+    assert_eq!(pat.exact(1), Some(0x5A));
+    assert_eq!(pat.exact(3), None);
+    let alt = YaraPattern::from_yara_style("( 48 | 49 ) 8B").unwrap();
+    assert_eq!((alt.exact(0), alt.exact(1)), (None, Some(0x8B)));
+
+    let errors = [
+        ("", 0),
+        ("4D [2-] 5A", 6),
+        ("4D [3-2] 5A", 6),
+        ("[2] 4D", 0),
+        ("4D [2]", 3),
+        ("4D ( 5A | ) 00", 10),
+        ("4D ( 5A 00", 10),
+        ("{ 4D 5A", 7),
+        ("4D 5A }", 6),
+        ("4D 5G", 4),
+        ("4D 5", 3),
+    ];
+    for (src, pos) in errors {
+        let err = YaraPattern::from_yara_style(src).unwrap_err();
+        assert!(
+            matches!(err, MfError::InvalidPattern(p) if p == pos),
+            "{src}: {err:?}"
+        );
+    }
+}
+
+#[test]
+fn test_yara_pattern_jumps() {
+    use memflex::YaraPattern;
+    use std::time::{Duration, Instant};
+
+    let pat = YaraPattern::from_yara_style("AA [0-65536] BB [0-65536] CC").unwrap();
+    let mut data = vec![0xBB; 0x20003];
+    data[0] = 0xAA;
+
+    let start = Instant::now();
+    assert!(!pat.matches(&data));
+    *data.last_mut().unwrap() = 0xCC;
+    assert!(pat.matches(&data));
+    data[0x20001] = 0xCC;
+    assert!(pat.matches(&data[..0x20002]));
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Long patterns don't recurse per byte
+    let long = YaraPattern::from_yara_style(&"90 ".repeat(100_000)).unwrap();
+    assert!(long.matches(&[0x90; 100_000]));
+}
+
+#[test]
+fn test_masked_patterns() {
+    use memflex::{find_pattern, DynPattern, MfError};
+
+    const MODRM: Pattern<8> = ida_pat!("48 8B ?5 ?? ?? ?? ?? E8");
+    assert!(MODRM.matches(b"\x48\x8B\x05\x11\x22\x33\x44\xE8"));
+    assert!(MODRM.matches(b"\x48\x8B\x15\x11\x22\x33\x44\xE8"));
+    assert!(!MODRM.matches(b"\x48\x8B\x06\x11\x22\x33\x44\xE8"));
+    assert_eq!(MODRM.to_ida_style(), "48 8B ?5 ? ? ? ? E8");
+
+    let rex = peid_pat!("4? B8:F8");
+    assert!(rex.matches(b"\x49\xBB"));
+    assert!(!rex.matches(b"\x49\xC3"));
+    assert_eq!(rex.to_peid_style(), "4? B8:F8");
+    assert_eq!(rex.to_code_style().1, "??");
+
+    let dynamic = DynPattern::from_ida_style(&MODRM.to_ida_style()).unwrap();
+    assert_eq!(dynamic, DynPattern::from(MODRM));
+    assert_eq!(
+        DynPattern::from_ida_style("4? 00:F0 ?? FF:FF 00:00").unwrap(),
+        DynPattern::from(ida_pat!("4? 0? ? FF ?"))
+    );
+    assert!(matches!(
+        DynPattern::from_ida_style("48 ?G"),
+        Err(MfError::InvalidPattern(4))
+    ));
+    assert!(matches!(
+        DynPattern::from_ida_style("48 4?:F0"),
+        Err(MfError::InvalidPattern(3))
+    ));
+
+    let memory = [0x90, 0x48, 0x8B, 0x35, 0, 0, 0, 0, 0xE8, 0x90];
+    let found = unsafe { find_pattern(MODRM, memory.as_ptr(), memory.len()).collect::<Vec<_>>() };
+    assert_eq!(found, [unsafe { memory.as_ptr().add(1) }]);
+}
+
+#[test]
+fn test_pattern_captures() {
+    use memflex::{find_matches, DynPattern, MfError, MAX_CAPTURES};
+
+    const CALL: Pattern<7> = ida_pat!("E8 & ? ? ? ? 48 & 8B &");
+    assert_eq!(CALL.captures(), [1, 6, 7]);
+    assert_eq!(CALL.to_ida_style(), "E8 & ? ? ? ? 48 & 8B &");
+
+    let dynamic = DynPattern::from_peid_style(&CALL.to_peid_style()).unwrap();
+    assert_eq!(dynamic, DynPattern::from(CALL));
+    assert_eq!(dynamic.captures(), [1, 6, 7]);
+
+    let too_many = "& ".repeat(MAX_CAPTURES + 1) + "90";
+    assert!(matches!(
+        DynPattern::from_ida_style(&too_many),
+        Err(MfError::InvalidPattern(16))
+    ));
+
+    // call -0x10
+    let code = [0x90, 0xE8, 0xF0, 0xFF, 0xFF, 0xFF, 0x48, 0x8B];
+    let base = code.as_ptr() as usize;
+    let found = unsafe { find_matches(CALL, code.as_ptr(), code.len()).collect::<Vec<_>>() };
+    assert_eq!(found.len(), 1);
+
+    let m = found[0];
+    assert_eq!(m.address(), base + 1);
+    assert_eq!(m.captures(), 3);
+    assert_eq!(m.capture(2), base + 8);
+    assert_eq!(unsafe { m.read::<i32>(0) }, -0x10);
+    assert_eq!(unsafe { m.rip_relative(0, 5) }, base + 6 - 0x10);
+}
+
+struct ManyCaptures([usize; 9]);
+
+impl Matcher for ManyCaptures {
+    fn matches(&self, _: &[u8]) -> bool {
+        true
+    }
+
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn captures(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+#[test]
+#[should_panic(expected = "MAX_CAPTURES")]
+fn test_too_many_captures() {
+    memflex::Match::new(&ManyCaptures([0; 9]), 0);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_module_captures() {
+    use memflex::internal::find_matches_in_module;
+
+    static MARKER: [u8; 11] = [
+        0x4D, 0x46, 0x4C, 0x58, 0x48, 0x8B, 0x05, 0x20, 0x00, 0x00, 0x00,
+    ];
+
+    let exe = std::env::current_exe().unwrap();
+    let name = exe.file_name().unwrap().to_str().unwrap();
+    let pat = ida_pat!("4D 46 4C 58 48 8B 05 & ? ? 00 00");
+
+    let m = find_matches_in_module(pat, name)
+        .unwrap()
+        .find(|m| m.address() == MARKER.as_ptr() as usize)
+        .unwrap();
+    assert_eq!(unsafe { m.read::<u32>(0) }, 0x20);
+}
@@ -0,0 +1,236 @@
+#![cfg(unix)]
+mod common;
+
+use common::page_size;
+use memflex::{
+    internal::{allocate, free, protect},
+    types::Protection,
+    MemorySource, MfError, PointerChain,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Allocates 4 pages holding pointers to `target` at known offsets,
+/// the third page can't be read.
+fn holders(target: usize) -> (usize, Vec<(usize, usize)>) {
+    let start = allocate(None, page_size() * 4, Protection::RW).unwrap() as usize;
+    let put = |offset: usize, value: usize| {
+        unsafe { ((start + offset) as *mut usize).write(value) };
+        (start + offset, value)
+    };
+
+    let expected = vec![
+        put(0, target),
+        put(0x18, target + 0xFF),
+        put(page_size() * 3 + 0x40, target + 0x10),
+    ];
+    // Out of range, misaligned and unreadable
+    put(0x20, target + 0x100);
+    put(0x28, target.wrapping_sub(1));
+    unsafe { ((start + 0x31) as *mut usize).write_unaligned(target) };
+    put(page_size() * 2, target);
+    protect(start + page_size() * 2, page_size(), Protection::empty()).unwrap();
+
+    (start, expected)
+}
+
+fn check(source: &impl MemorySource) {
+    let target = allocate(None, page_size(), Protection::RW).unwrap() as usize;
+    let (start, expected) = holders(target);
+
+    let hits = source
+        .find_pointers(target, 0x100)
+        .unwrap()
+        .filter(|h| (start..start + page_size() * 4).contains(&h.address))
+        .collect::<Vec<_>>();
+
+    let found = hits
+        .iter()
+        .map(|h| (h.address, h.value))
+        .collect::<Vec<_>>();
+    assert_eq!(found, expected);
+    for hit in hits {
+        assert!(hit.region.prot.read());
+        assert_eq!(hit.region.from + hit.region_offset(), hit.address);
+        assert!(hit.address < hit.region.to);
+    }
+
+    let exact = source
+        .find_pointers(target, 0)
+        .unwrap()
+        .filter(|h| (start..start + page_size() * 4).contains(&h.address))
+        .map(|h| h.address)
+        .collect::<Vec<_>>();
+    assert_eq!(exact, [start]);
+
+    protect(start + page_size() * 2, page_size(), Protection::RW).unwrap();
+    free(start, page_size() * 4).unwrap();
+    free(target, page_size()).unwrap();
+}
+
+common::source_tests!(test_find_pointers, test_remote_find_pointers: check);
+
+/// Returns a static that isn't used by any other check, checks run in parallel.
+fn static_root() -> &'static AtomicUsize {
+    // Large enough to reach past the last page of the executable that is mapped from the file
+    static ROOTS: [AtomicUsize; 0x4000] = [const { AtomicUsize::new(0) }; 0x4000];
+    static USED: AtomicUsize = AtomicUsize::new(0);
+    &ROOTS[ROOTS.len() - 1 - USED.fetch_add(1, Ordering::SeqCst)]
+}
+
+/// File name of the test executable, statics of the tests are in its `.bss`.
+fn executable() -> String {
+    let exe = std::env::current_exe().unwrap();
+    exe.file_name().unwrap().to_str().unwrap().into()
+}
+
+fn check_paths(source: &impl MemorySource) {
+    let root = static_root();
+    let object = Box::new([0_usize; 8]);
+    let first = Box::new([0_u64; 4]);
+    let target = &first[1] as *const u64 as usize;
+
+    // ROOT -> object, object[2] -> first, first + 8 is the target
+    let slot = &object[2] as *const usize as *mut usize;
+    unsafe { slot.write_volatile(&*first as *const _ as usize) };
+    root.store(&*object as *const _ as usize, Ordering::SeqCst);
+
+    let static_root = root;
+    let root = root as *const _ as usize;
+    let module = source.find_module(&executable()).unwrap();
+    let expected = PointerChain {
+        module: Some(module.name.clone()),
+        offset: root - module.base as usize,
+        offsets: vec![0x10, 0x8],
+    };
+
+    let paths = source
+        .find_pointer_paths(target, 2, 0x20)
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert!(paths.contains(&expected), "{expected} not in {paths:?}");
+    assert!(paths
+        .windows(2)
+        .all(|w| w[0].offsets.len() <= w[1].offsets.len()));
+    assert_eq!(expected.resolve(source).unwrap(), target);
+    assert_eq!(
+        expected.to_string().parse::<PointerChain>().unwrap(),
+        expected
+    );
+
+    let shallow = source.find_pointer_paths(target, 1, 0x20).unwrap();
+    assert!(shallow.into_iter().all(|p| p.offsets.len() == 1));
+
+    // Chain survives the target moving around
+    let second = Box::new([0_u64; 4]);
+    unsafe { slot.write_volatile(&*second as *const _ as usize) };
+    let moved = &second[1] as *const u64 as usize;
+    assert!(expected.points_to(source, moved));
+    assert!(!expected.points_to(source, target));
+
+    let broken = PointerChain {
+        module: Some("not-loaded.so".into()),
+        ..expected.clone()
+    };
+    assert!(matches!(
+        broken.resolve(source),
+        Err(MfError::ModuleNotFound)
+    ));
+
+    static_root.store(usize::MAX & !0xFFF, Ordering::SeqCst);
+    assert!(matches!(
+        expected.resolve(source),
+        Err(MfError::InvalidPointer { level: 1, address }) if address == (usize::MAX & !0xFFF) + 0x10
+    ));
+}
+
+common::source_tests!(test_find_pointer_paths, test_remote_find_pointer_paths: check_paths);
+
+#[test]
+fn test_pointer_chain_parse() {
+    let chain = "[[ld-linux-x86-64.so.2+0x1A2B0]+0x18]+0x30"
+        .parse::<PointerChain>()
+        .unwrap();
+    assert_eq!(chain.module.as_deref(), Some("ld-linux-x86-64.so.2"));
+    assert_eq!(
+        (chain.offset, &chain.offsets[..]),
+        (0x1A2B0, &[0x18, 0x30][..])
+    );
+    assert_eq!(
+        chain.to_string(),
+        "[[ld-linux-x86-64.so.2+0x1A2B0]+0x18]+0x30"
+    );
+
+    let chain = " [ [libstdc++.so.6] - 8 ] "
+        .parse::<PointerChain>()
+        .unwrap();
+    assert_eq!(chain.module.as_deref(), Some("libstdc++.so.6"));
+    assert_eq!(chain.offset, 0);
+    assert_eq!(chain.offsets, [8_usize.wrapping_neg(), 0]);
+    assert_eq!(chain.to_string(), "[[libstdc++.so.6+0x0]-0x8]+0x0");
+
+    let chain = "[0x7F00+16]+0x8".parse::<PointerChain>().unwrap();
+    assert_eq!(chain.module, None);
+    assert_eq!((chain.offset, &chain.offsets[..]), (0x7F10, &[8][..]));
+    assert_eq!(chain.to_string(), "[0x7F10]+0x8");
+    assert_eq!(chain.to_string().parse::<PointerChain>().unwrap(), chain);
+
+    for (expr, pos) in [
+        ("", 0),
+        ("[]", 1),
+        ("[[game]+0x10", 12),
+        ("[game]]", 6),
+        ("[game]+0xZZ", 6),
+        ("[game] 0x10", 7),
+    ] {
+        assert!(
+            matches!(expr.parse::<PointerChain>(), Err(MfError::InvalidExpression(p)) if p == pos),
+            "{expr}"
+        );
+    }
+}
+
+fn check_chain(source: &impl MemorySource) {
+    let root = static_root();
+    let object = Box::new([0_usize; 4]);
+    let field = Box::new([0_u64; 8]);
+    let slot = &object[1] as *const usize as *mut usize;
+    unsafe { slot.write_volatile(&*field as *const _ as usize) };
+
+    // ROOT -> object, object[1] -> field, field + 0x30 is the target
+    root.store(&*object as *const _ as usize, Ordering::SeqCst);
+
+    let root_address = root as *const _ as usize;
+    let module = source.find_module(&executable()).unwrap();
+
+    let expr = format!(
+        "[[{}+{:#x}]+0x8]+0x30",
+        module.name,
+        root_address - module.base as usize
+    );
+    let chain = expr.parse::<PointerChain>().unwrap();
+    let target = &field[6] as *const u64 as usize;
+    assert_eq!(chain.resolve(source).unwrap(), target);
+
+    let absolute = format!("[[{root_address:#x}]+0x8]+0x30")
+        .parse::<PointerChain>()
+        .unwrap();
+    assert_eq!(absolute.resolve(source).unwrap(), target);
+
+    let missing = PointerChain {
+        module: Some("not-loaded.so".into()),
+        ..chain.clone()
+    };
+    assert!(matches!(
+        missing.resolve(source),
+        Err(MfError::ModuleNotFound)
+    ));
+
+    let bad = usize::MAX & !0xFFF;
+    root.store(bad, Ordering::SeqCst);
+    assert!(matches!(
+        chain.resolve(source),
+        Err(MfError::InvalidPointer { level: 1, address }) if address == bad + 8
+    ));
+}
+
+common::source_tests!(test_pointer_chain, test_remote_pointer_chain: check_chain);
@@ -0,0 +1,36 @@
+#![cfg(unix)]
+use memflex::internal::find_module_by_name;
+
+// Macros take module names as literals, glibc's `libc.so.6` is fixed by its ABI.
+#[cfg(target_env = "gnu")]
+mod glibc {
+    use memflex::internal::find_module_by_name;
+
+    memflex::global! {
+        extern LIBC_BASE: u8 = "libc.so.6"#0;
+    }
+
+    memflex::function! {
+        fn ELF_HEADER() = "libc.so.6"%"7F 45 4C 46 02 01 01";
+    }
+
+    #[test]
+    fn test_default_resolver() {
+        let libc = find_module_by_name("libc.so.6").unwrap();
+
+        assert_eq!(LIBC_BASE.address(), libc.base as usize);
+        assert_eq!(ELF_HEADER.address(), libc.base as usize);
+    }
+}
+
+#[test]
+fn test_find_main_module() {
+    let exe = std::env::current_exe().unwrap();
+    let name = exe.file_name().unwrap().to_str().unwrap();
+
+    let module = find_module_by_name(name).unwrap();
+    let here = test_find_main_module as *const () as usize;
+    assert!(here > module.base as usize && here < module.base as usize + module.size);
+    // Names of objects are case sensitive
+    assert!(find_module_by_name(&name.to_uppercase()).is_none());
+}
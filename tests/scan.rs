@@ -0,0 +1,212 @@
+use memflex::{
+    find_pattern, find_pattern_set, find_pattern_with, ida_pat, DynPattern, Matcher, PatternSet,
+    ScanOptions, YaraPattern,
+};
+
+fn haystack(len: usize) -> Vec<u8> {
+    let mut state = 0x2545F4914F6CDD1D_u64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 7) as u8 * 0x11
+        })
+        .collect()
+}
+
+fn naive(pat: impl Matcher, data: &[u8]) -> Vec<usize> {
+    data.windows(pat.len())
+        .enumerate()
+        .filter_map(|(i, w)| pat.matches(w).then_some(i))
+        .collect()
+}
+
+fn fast(pat: impl Matcher, data: &[u8]) -> Vec<usize> {
+    unsafe {
+        find_pattern(pat, data.as_ptr(), data.len())
+            .map(|p| p as usize - data.as_ptr() as usize)
+            .collect()
+    }
+}
+
+#[test]
+fn test_scanner_agrees_with_naive() {
+    let mut data = haystack(0x10000);
+    data[3..7].copy_from_slice(&[0xAB, 0xCD, 0x00, 0xEF]);
+    data[0xFFFC..].copy_from_slice(&[0xAB, 0xCD, 0x11, 0xEF]);
+
+    let patterns = [
+        "AB CD ? EF",
+        "11 22 ? 33",
+        "00 00",
+        "? ? 66",
+        "? ?",
+        "66 ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? 66",
+    ];
+
+    for pat in patterns {
+        let pat = DynPattern::from_ida_style(pat).unwrap();
+        let expected = naive(&pat, &data);
+        assert!(!expected.is_empty());
+        assert_eq!(fast(&pat, &data), expected, "{}", pat.to_ida_style());
+    }
+
+    assert_eq!(fast(ida_pat!("AB CD ? EF"), &data), [3, 0xFFFC]);
+    assert_eq!(fast(&[0x77_u8; 4][..], &data), []);
+}
+
+#[test]
+fn test_scanner_overlapping_matches() {
+    let data = [0xCC_u8; 100];
+    assert_eq!(
+        fast(ida_pat!("CC CC CC"), &data),
+        (0..98).collect::<Vec<_>>()
+    );
+    assert_eq!(fast(ida_pat!("CC CC CC"), &data[..2]), []);
+}
+
+#[test]
+fn test_pattern_set_agrees_with_naive() {
+    let mut data = haystack(0x10000);
+    data[3..7].copy_from_slice(&[0xAB, 0xCD, 0x00, 0xEF]);
+
+    let patterns = [
+        "AB CD ? EF",
+        "CD ? EF",
+        "11 22 ? 33",
+        "00 00",
+        "33 33 33 33 33 33 33 33 33 33",
+        "? ? 66",
+        "? ?",
+        "5? ? ?7",
+        "66 ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? ? 66",
+    ];
+
+    let mut set = PatternSet::new();
+    let mut expected = vec![];
+    for (id, pat) in patterns.into_iter().enumerate() {
+        let pat = DynPattern::from_ida_style(pat).unwrap();
+        expected.extend(naive(&pat, &data).into_iter().map(|o| (id, o)));
+        assert_eq!(set.add(pat), id);
+    }
+
+    let mut found = unsafe {
+        find_pattern_set(&set, data.as_ptr(), data.len())
+            .map(|(id, m)| (id, m.address() - data.as_ptr() as usize))
+            .collect::<Vec<_>>()
+    };
+    found.sort();
+    expected.sort();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn test_variable_length_patterns() {
+    let mut data = haystack(0x4000);
+    // Only the short alternative fits at the very end
+    data[0x3FFD..].copy_from_slice(&[0x4D, 0x5A, 0x77]);
+
+    let patterns = [
+        "4D 5A ( 77 | 66 [4-8] 66 )",
+        "( 11 | 22 ) [1-3] 33",
+        "?? [0-2] ( 44 55 | 66 )",
+    ];
+
+    let mut set = PatternSet::new();
+    let mut expected_set = vec![];
+    for (id, pat) in patterns.into_iter().enumerate() {
+        let pat = YaraPattern::from_yara_style(pat).unwrap();
+        let expected = (0..=data.len() - pat.min_len())
+            .filter(|&i| pat.matches(&data[i..data.len().min(i + pat.len())]))
+            .collect::<Vec<_>>();
+
+        assert!(!expected.is_empty());
+        assert_eq!(
+            fast(&pat, &data),
+            expected,
+            "{}",
+            pat.to_yara_style().unwrap()
+        );
+        expected_set.extend(expected.into_iter().map(|o| (id, o)));
+        set.add(pat);
+    }
+
+    let pat = YaraPattern::from_yara_style(patterns[0]).unwrap();
+    assert_eq!(fast(&pat, &data).last(), Some(&0x3FFD));
+
+    let mut found = set.scan(&data).collect::<Vec<_>>();
+    found.sort();
+    expected_set.sort();
+    assert_eq!(found, expected_set);
+}
+
+#[test]
+fn test_scan_options() {
+    let data = haystack(0x4000);
+    let base = data.as_ptr() as usize;
+    let with = |pat: &DynPattern, options| unsafe {
+        find_pattern_with(pat, data.as_ptr(), data.len(), options)
+            .map(|p| p as usize - base)
+            .collect::<Vec<_>>()
+    };
+
+    for pat in ["33 ? 66", "? ?", "00 00 00"] {
+        let pat = DynPattern::from_ida_style(pat).unwrap();
+        let all = naive(&pat, &data);
+
+        for align in [0, 1, 2, 16] {
+            let aligned = all
+                .iter()
+                .copied()
+                .filter(|o| (base + o).is_multiple_of(align.max(1)))
+                .collect::<Vec<_>>();
+            assert!(!aligned.is_empty());
+
+            let options = ScanOptions {
+                align,
+                ..Default::default()
+            };
+            assert_eq!(with(&pat, options), aligned);
+
+            let reverse = ScanOptions {
+                reverse: true,
+                ..options
+            };
+            let mut expected = aligned.clone();
+            expected.reverse();
+            assert_eq!(with(&pat, reverse), expected);
+
+            let limited = ScanOptions {
+                max_results: Some(3),
+                ..reverse
+            };
+            assert_eq!(with(&pat, limited), &expected[..expected.len().min(3)]);
+        }
+    }
+
+    // Closest prologue before an address
+    let mut code = vec![0xCC_u8; 0x100];
+    code[0x10..0x14].copy_from_slice(&[0x55, 0x48, 0x89, 0xE5]);
+    code[0x40..0x44].copy_from_slice(&[0x55, 0x48, 0x89, 0xE5]);
+    let options = ScanOptions {
+        reverse: true,
+        max_results: Some(1),
+        ..Default::default()
+    };
+    let found = unsafe {
+        find_pattern_with(ida_pat!("55 48 89 E5"), code.as_ptr(), 0x80, options)
+            .map(|p| p as usize - code.as_ptr() as usize)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(found, [0x40]);
+
+    let pat = YaraPattern::from_yara_style("4D 5A ( 77 | 66 [4-8] 66 )").unwrap();
+    let tail = [0x00, 0x4D, 0x5A, 0x77];
+    let options = ScanOptions {
+        reverse: true,
+        ..Default::default()
+    };
+    let found = unsafe { find_pattern_with(&pat, tail.as_ptr(), tail.len(), options).count() };
+    assert_eq!(found, 1);
+}
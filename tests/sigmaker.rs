@@ -0,0 +1,58 @@
+#![cfg(unix)]
+use memflex::internal::{find_pattern_in_module, make_signature};
+use std::ptr::addr_of_mut;
+
+const EXPECTED: &str = "48 8D 0D ? ? ? ? E8 ? ? ? ? 48 B8 ? ? ? ? ? ? ? ? B9 37 0D F0 13";
+
+static mut CODE: [u8; 28] = [
+    0x48, 0x8D, 0x0D, 0x10, 0x00, 0x00, 0x00, // lea rcx, [rip + 0x10]
+    0xE8, 0x00, 0x01, 0x00, 0x00, // call rel32
+    0x48, 0xB8, 0, 0, 0, 0, 0, 0, 0, 0, // mov rax, imm64
+    0xB9, 0x37, 0x0D, 0xF0, 0x13, // mov ecx, 0x13F00D37
+    0xC3, // ret
+];
+
+/// Patches `mov rax, imm64` to hold an address inside the module.
+fn code() -> usize {
+    unsafe {
+        let code = addr_of_mut!(CODE) as usize;
+        ((code + 14) as *mut usize).write_unaligned(code);
+        code
+    }
+}
+
+#[test]
+fn test_make_signature() {
+    let address = code();
+    let exe = std::env::current_exe().unwrap();
+    let exe = exe.file_name().unwrap().to_str().unwrap();
+
+    let sig = make_signature(address as _, 64).unwrap();
+    let ida = sig.pattern.to_ida_style();
+    assert!(sig.unique);
+    assert!(EXPECTED.starts_with(&ida), "{ida}");
+    assert!(ida.starts_with("48 8D 0D ? ? ? ? E8 ? ? ? ?"), "{ida}");
+
+    let found = find_pattern_in_module(&sig.pattern, exe)
+        .unwrap()
+        .map(|p| p as usize)
+        .collect::<Vec<_>>();
+    assert_eq!(found, [address]);
+
+    let short = make_signature(address as _, 3).unwrap();
+    assert!(!short.unique);
+    assert_eq!(short.pattern.to_ida_style(), "48 8D 0D");
+
+    assert!(make_signature(std::ptr::null(), 64).is_none());
+}
+
+#[cfg(feature = "external")]
+#[test]
+fn test_remote_make_signature() {
+    let address = code();
+    let process = memflex::external::find_process_by_id(std::process::id()).unwrap();
+
+    let sig = process.make_signature(address, 64).unwrap();
+    assert!(sig.unique);
+    assert_eq!(sig, make_signature(address as _, 64).unwrap());
+}
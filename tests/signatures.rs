@@ -0,0 +1,68 @@
+#![cfg(unix)]
+mod common;
+
+use common::libc_name;
+use memflex::{
+    ida_pat,
+    internal::{check_signatures, find_module_by_name},
+    types::ModuleInfo,
+    SignatureChecker, SignatureStatus,
+};
+
+fn checker() -> SignatureChecker {
+    let mut checker = SignatureChecker::new();
+    checker.add("elf_header", ida_pat!("7F 45 4C 46 02 01 01"));
+    checker.add("missing", ida_pat!("DE AD BE EF DE AD BE EF 13 37"));
+    checker.add("zeroes", ida_pat!("00 00 00 00"));
+    checker
+}
+
+#[test]
+fn test_check_signatures() {
+    let libc = find_module_by_name(&libc_name()).unwrap();
+    let report = check_signatures(&checker(), &libc).unwrap();
+
+    let header = report.get("elf_header").unwrap();
+    assert_eq!(header.status(), SignatureStatus::Unique);
+    assert_eq!(header.offsets, [0]);
+    assert_eq!(
+        report.get("missing").unwrap().status(),
+        SignatureStatus::Missing
+    );
+    assert_eq!(
+        report.get("zeroes").unwrap().status(),
+        SignatureStatus::Ambiguous
+    );
+
+    assert!(!report.is_healthy());
+    assert_eq!(
+        report.broken().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+        ["missing", "zeroes"]
+    );
+
+    let bogus = ModuleInfo {
+        base: 0x1000 as _,
+        size: 0x1000,
+    };
+    assert!(check_signatures(&checker(), &bogus).is_err());
+
+    let resized = ModuleInfo {
+        size: libc.size + 0x1000,
+        ..libc
+    };
+    assert!(check_signatures(&checker(), &resized).is_err());
+}
+
+#[cfg(feature = "external")]
+#[test]
+fn test_remote_check_signatures() {
+    let process = memflex::external::find_process_by_id(std::process::id()).unwrap();
+    let module = process.find_module(&libc_name()).unwrap();
+
+    let report = process.check_signatures(&checker(), &module);
+    assert_eq!(report.get("elf_header").unwrap().offsets, [0]);
+    assert_eq!(
+        report.get("missing").unwrap().status(),
+        SignatureStatus::Missing
+    );
+}
@@ -0,0 +1,104 @@
+#![cfg(unix)]
+mod common;
+
+use common::page_size;
+use memflex::{
+    internal::{allocate, free, protect, CurrentProcess},
+    types::Protection,
+    Change, MemorySource, Snapshot,
+};
+use std::iter::once;
+
+fn put(address: usize, bytes: &[u8]) {
+    unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), address as *mut u8, bytes.len()) };
+}
+
+fn change(address: usize, old: &[u8], new: &[u8]) -> Change {
+    Change {
+        address,
+        old: old.to_vec(),
+        new: new.to_vec(),
+    }
+}
+
+fn check(source: &impl MemorySource) {
+    let start = allocate(None, page_size() * 4, Protection::RW).unwrap() as usize;
+    put(start + 0x10, &[1, 2, 3, 4]);
+    protect(start + page_size() * 2, page_size(), Protection::empty()).unwrap();
+
+    // Overlapping ranges are merged, the unreadable page is left out
+    let before = Snapshot::capture(
+        source,
+        [
+            start..start + page_size() * 2,
+            start + page_size()..start + page_size() * 4,
+        ],
+    );
+    let ranges = before
+        .ranges()
+        .iter()
+        .map(|r| r.range())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ranges,
+        [
+            start..start + page_size() * 2,
+            start + page_size() * 3..start + page_size() * 4
+        ]
+    );
+    assert_eq!(before.len(), page_size() * 3);
+    assert_eq!(before.get(start + 0x10, 4), Some(&[1, 2, 3, 4][..]));
+    assert_eq!(before.get(start + page_size() * 2 - 2, 4), None);
+
+    assert!(before.diff_live(source).is_empty());
+
+    put(start + 0x11, &[0xAA, 0xBB]);
+    put(start + page_size() - 1, &[5, 6]);
+    put(start + page_size() * 3 + 8, &[7]);
+    let expected = [
+        change(start + 0x11, &[2, 3], &[0xAA, 0xBB]),
+        change(start + page_size() - 1, &[0, 0], &[5, 6]),
+        change(start + page_size() * 3 + 8, &[0], &[7]),
+    ];
+    assert_eq!(before.diff_live(source), expected);
+
+    let after = Snapshot::capture(source, once(start..start + page_size() * 4));
+    assert_eq!(before.diff(&after), expected);
+    assert!(after.diff(&after).is_empty());
+
+    // Only memory captured by both is compared
+    let partial = Snapshot::capture(source, once(start + 0x12..start + 0x20));
+    assert_eq!(before.diff(&partial), [change(start + 0x12, &[3], &[0xBB])]);
+    assert_eq!(partial.diff(&before), [change(start + 0x12, &[0xBB], &[3])]);
+
+    // Memory that became unreadable isn't compared
+    protect(start, page_size(), Protection::empty()).unwrap();
+    assert_eq!(
+        before.diff_live(source),
+        [change(start + page_size(), &[0], &[6]), expected[2].clone()]
+    );
+
+    protect(start, page_size() * 4, Protection::RW).unwrap();
+    free(start, page_size() * 4).unwrap();
+}
+
+common::source_tests!(test_snapshot, test_remote_snapshot: check);
+
+#[test]
+fn test_snapshot_file() {
+    let data = Box::new(*b"snapshot on disk");
+    let address = data.as_ptr() as usize;
+    let snapshot = Snapshot::capture(&CurrentProcess, once(address..address + data.len()));
+
+    let path = std::env::temp_dir().join(format!("memflex-{}.snap", std::process::id()));
+    snapshot.save(&path).unwrap();
+    let loaded = Snapshot::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, snapshot);
+
+    let mut bytes = vec![];
+    snapshot.write_to(&mut bytes).unwrap();
+    assert!(Snapshot::read_from(&bytes[..bytes.len() - 1]).is_err());
+    bytes[0] = b'X';
+    assert!(Snapshot::read_from(&bytes[..]).is_err());
+}
@@ -4,7 +4,7 @@ use memflex::{types::win::UnicodeString, unicode_string};
 #[test]
 #[cfg(windows)]
 fn test_unicode_macros() {
-    const TEST_STRING: &'static str = "Memflex Unicode String";
+    const TEST_STRING: &str = "Memflex Unicode String";
     const UNICODE_STRING: UnicodeString = unicode_string!(TEST_STRING);
 
     assert_eq!(UNICODE_STRING.len(), TEST_STRING.len());
@@ -0,0 +1,191 @@
+#![cfg(unix)]
+mod common;
+
+use common::page_size;
+use memflex::{
+    internal::{allocate, free, protect, CurrentProcess},
+    types::Protection,
+    MemorySource, MfError, ScanCondition, Value, ValueKind, ValueScan, ValueScanOptions,
+};
+
+/// Writes `value` at `address` as a volatile write, so it isn't kept in registers.
+fn put<T>(address: usize, value: T) {
+    unsafe { (address as *mut T).write_volatile(value) };
+}
+
+fn within(scan: &ValueScan, start: usize, len: usize) -> Vec<(usize, Value)> {
+    scan.results()
+        .filter(|(a, _)| (start..start + len).contains(a))
+        .collect()
+}
+
+fn check_ints(source: &impl MemorySource) {
+    let start = allocate(None, page_size() * 3, Protection::RW).unwrap() as usize;
+    put(start + 0x10, 0x1337_C0DE_u32);
+    put(start + 0x20, 0x1337_C0DE_u32);
+    put(start + 0x30, 0x1337_C0DE_u32);
+    // Misaligned, straddling pages and unreadable
+    unsafe { ((start + 0x41) as *mut u32).write_unaligned(0x1337_C0DE) };
+    put(start + page_size() * 2 - 4, 0x1337_C0DE_u32);
+    put(start + page_size() * 2 + 8, 0x1337_C0DE_u32);
+    protect(start + page_size() * 2, page_size(), Protection::empty()).unwrap();
+
+    let mut scan = ValueScan::exact(source, 0x1337_C0DE_u32.into(), Default::default()).unwrap();
+    assert_eq!(scan.kind(), ValueKind::U32);
+    let expected =
+        [0x10, 0x20, 0x30, page_size() * 2 - 4].map(|o| (start + o, Value::U32(0x1337_C0DE)));
+    assert_eq!(within(&scan, start, page_size() * 3), expected);
+    assert!(scan.len() >= 4);
+
+    let unaligned = ValueScanOptions {
+        align: 1,
+        ..Default::default()
+    };
+    let bytes = ValueScan::exact(source, 0x1337_C0DE_u32.into(), unaligned).unwrap();
+    assert_eq!(within(&bytes, start, page_size() * 3).len(), 5);
+
+    put(start + 0x10, 0x1337_C0DF_u32);
+    put(start + 0x20, 0x1337_C0DA_u32);
+    scan.next_scan(source, &ScanCondition::Changed).unwrap();
+    assert_eq!(
+        within(&scan, start, page_size() * 3),
+        [
+            (start + 0x10, Value::U32(0x1337_C0DF)),
+            (start + 0x20, Value::U32(0x1337_C0DA))
+        ]
+    );
+
+    scan.next_scan(source, &ScanCondition::Unchanged).unwrap();
+    assert_eq!(within(&scan, start, page_size() * 3).len(), 2);
+
+    put(start + 0x20, 0x1337_C0D9_u32);
+    let mut down = ValueScan::exact(source, 0x1337_C0D9_u32.into(), Default::default()).unwrap();
+    put(start + 0x20, 0x1337_C0D8_u32);
+    down.next_scan(source, &ScanCondition::Decreased).unwrap();
+    down.next_scan(source, &ScanCondition::Delta(0_u32.into()))
+        .unwrap();
+    put(start + 0x20, 0x1337_C0D7_u32);
+    down.next_scan(source, &ScanCondition::Delta(u32::MAX.into()))
+        .unwrap();
+    assert_eq!(
+        within(&down, start, page_size() * 3),
+        [(start + 0x20, Value::U32(0x1337_C0D7))]
+    );
+
+    // Results that became unreadable are dropped
+    protect(start, page_size(), Protection::empty()).unwrap();
+    scan.next_scan(source, &ScanCondition::Unchanged).unwrap();
+    assert!(within(&scan, start, page_size() * 3).is_empty());
+
+    protect(start, page_size() * 3, Protection::RW).unwrap();
+    free(start, page_size() * 3).unwrap();
+}
+
+fn check_unknown(source: &impl MemorySource) {
+    let start = allocate(None, page_size() * 2, Protection::RW).unwrap() as usize;
+    for i in 0..page_size() / 4 {
+        put(start + i * 8, i as i16 - 0x100);
+    }
+
+    let mut scan = ValueScan::unknown(source, ValueKind::I16, Default::default()).unwrap();
+    assert_eq!(within(&scan, start, page_size() * 2).len(), page_size());
+
+    put(start + 0x80, -0x1000_i16);
+    put(start + 0x88, 0x7000_i16);
+    put(start + page_size() + 6, 5_i16);
+    scan.next_scan(source, &ScanCondition::Increased).unwrap();
+    assert_eq!(
+        within(&scan, start, page_size() * 2),
+        [
+            (start + 0x88, Value::I16(0x7000)),
+            (start + page_size() + 6, Value::I16(5))
+        ]
+    );
+
+    scan.next_scan(source, &ScanCondition::Exact(5_i16.into()))
+        .unwrap();
+    assert_eq!(
+        within(&scan, start, page_size() * 2),
+        [(start + page_size() + 6, Value::I16(5))]
+    );
+
+    free(start, page_size() * 2).unwrap();
+}
+
+fn check_floats_and_bytes(source: &impl MemorySource) {
+    let start = allocate(None, page_size(), Protection::RW).unwrap() as usize;
+    put(start, 100.25_f32);
+    put(start + 8, 100.5_f64);
+    put(start + 0x20, *b"memflex!");
+    unsafe { ((start + 0x31) as *mut [u8; 8]).write(*b"memflex!") };
+
+    let tolerance = ValueScanOptions {
+        tolerance: 0.5,
+        ..Default::default()
+    };
+    let mut floats = ValueScan::exact(source, 100.0_f32.into(), tolerance).unwrap();
+    assert_eq!(
+        within(&floats, start, page_size()),
+        [(start, Value::F32(100.25))]
+    );
+
+    put(start, 99.0_f32);
+    floats
+        .next_scan(source, &ScanCondition::Delta((-1.0_f32).into()))
+        .unwrap();
+    assert_eq!(
+        within(&floats, start, page_size()),
+        [(start, Value::F32(99.0))]
+    );
+
+    let doubles = ValueScan::exact(source, 101.0_f64.into(), tolerance).unwrap();
+    assert_eq!(
+        within(&doubles, start, page_size()),
+        [(start + 8, Value::F64(100.5))]
+    );
+
+    let mut bytes = ValueScan::exact(source, b"memflex!"[..].into(), Default::default()).unwrap();
+    assert_eq!(bytes.kind(), ValueKind::Bytes(8));
+    assert_eq!(
+        within(&bytes, start, page_size()),
+        [
+            (start + 0x20, Value::Bytes(b"memflex!".to_vec())),
+            (start + 0x31, Value::Bytes(b"memflex!".to_vec()))
+        ]
+    );
+
+    put(start + 0x20, *b"memflex?");
+    bytes
+        .next_scan(source, &ScanCondition::Exact(b"memflex?"[..].into()))
+        .unwrap();
+    assert_eq!(within(&bytes, start, page_size()).len(), 1);
+
+    free(start, page_size()).unwrap();
+}
+
+fn check(source: &impl MemorySource) {
+    check_ints(source);
+    check_unknown(source);
+    check_floats_and_bytes(source);
+}
+
+common::source_tests!(test_value_scan, test_remote_value_scan: check);
+
+#[test]
+fn test_invalid_value_scans() {
+    let source = &CurrentProcess;
+    let empty = ValueScan::unknown(source, ValueKind::Bytes(0), Default::default());
+    assert!(matches!(empty, Err(MfError::InvalidValue)));
+    let empty = ValueScan::exact(source, b""[..].into(), Default::default());
+    assert!(matches!(empty, Err(MfError::InvalidValue)));
+
+    let mut scan = ValueScan::exact(source, 1_u8.into(), Default::default()).unwrap();
+    let len = scan.len();
+    let mismatch = scan.next_scan(source, &ScanCondition::Exact(1_u16.into()));
+    assert!(matches!(mismatch, Err(MfError::InvalidValue)));
+    assert_eq!(scan.len(), len);
+
+    let mut bytes = ValueScan::exact(source, b"mf"[..].into(), Default::default()).unwrap();
+    let ordered = bytes.next_scan(source, &ScanCondition::Increased);
+    assert!(matches!(ordered, Err(MfError::InvalidValue)));
+}
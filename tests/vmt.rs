@@ -0,0 +1,159 @@
+#![cfg(unix)]
+use memflex::{
+    internal::ShadowVmt,
+    types::{VmtEntry, VmtPtr},
+};
+use std::sync::Mutex;
+
+#[repr(C)]
+struct FooVmt {
+    get_health: extern "C" fn(&Foo) -> i32,
+    set_health: extern "C" fn(&mut Foo, i32) -> i32,
+}
+
+#[repr(C)]
+struct Foo {
+    vmt: *const FooVmt,
+    health: i32,
+}
+
+/// Table with RTTI words before it like the ones compilers emit, leaked so it can be written to.
+#[repr(C)]
+struct FooTable {
+    rtti: [usize; 2],
+    vmt: FooVmt,
+}
+
+fn table() -> *mut FooVmt {
+    let table = Box::leak(Box::new(FooTable {
+        rtti: [0, 0x1234],
+        vmt: FooVmt {
+            get_health,
+            set_health,
+        },
+    }));
+    &mut table.vmt
+}
+
+extern "C" fn get_health(object: &Foo) -> i32 {
+    object.health
+}
+
+extern "C" fn set_health(object: &mut Foo, new: i32) -> i32 {
+    std::mem::replace(&mut object.health, new)
+}
+
+#[repr(C, align(8))]
+struct CFoo([u8; 0x10]);
+
+memflex::interface! {
+    trait IFoo impl for CFoo {
+        extern fn get_health() -> i32 = #0;
+        extern fn set_health(new: i32) -> i32 = #1;
+    }
+}
+
+type SetHealth = for<'a> extern "C" fn(&'a CFoo, i32) -> i32;
+static ORIGINAL: Mutex<Option<SetHealth>> = Mutex::new(None);
+
+extern "C" fn set_health_doubled(this: &CFoo, new: i32) -> i32 {
+    ORIGINAL.lock().unwrap().unwrap()(this, new * 2)
+}
+
+extern "C" fn get_health_fixed(_: &CFoo) -> i32 {
+    1337
+}
+
+fn view(object: &mut Foo) -> &CFoo {
+    unsafe { &*(object as *mut Foo as *const CFoo) }
+}
+
+#[test]
+fn test_vmt_entry() {
+    assert_eq!(CFoo::GET_HEALTH.index(), 0);
+    assert_eq!(CFoo::SET_HEALTH.index(), 1);
+    let entry: VmtEntry<SetHealth> = CFoo::SET_HEALTH;
+    assert_eq!(entry.index(), 1);
+}
+
+#[test]
+fn test_vmt_hook() {
+    let swapped = table();
+    let mut object = Foo {
+        vmt: swapped,
+        health: 100,
+    };
+    let mut other = Foo {
+        vmt: swapped,
+        health: 100,
+    };
+    let vmt = unsafe { &*(&object as *const Foo as *const VmtPtr) };
+
+    let mut hook = unsafe { vmt.hook(CFoo::SET_HEALTH, set_health_doubled) }.unwrap();
+    *ORIGINAL.lock().unwrap() = Some(hook.original());
+    assert_eq!(hook.entry().index(), 1);
+
+    assert_eq!(view(&mut object).set_health(10), 100);
+    assert_eq!(view(&mut object).get_health(), 20);
+    // Table is shared with other objects
+    assert_eq!(view(&mut other).set_health(10), 100);
+    assert_eq!(other.health, 20);
+
+    hook.disable().unwrap();
+    assert!(!hook.is_enabled());
+    view(&mut object).set_health(10);
+    assert_eq!(object.health, 10);
+    hook.enable().unwrap();
+    view(&mut object).set_health(10);
+    assert_eq!(object.health, 20);
+
+    hook.restore().unwrap();
+    view(&mut object).set_health(10);
+    assert_eq!(object.health, 10);
+    assert_eq!(
+        unsafe { (*swapped).set_health } as usize,
+        set_health as *const () as usize
+    );
+}
+
+#[test]
+fn test_shadow_vmt() {
+    let shadowed = table();
+    let mut object = Foo {
+        vmt: shadowed,
+        health: 100,
+    };
+    let mut other = Foo {
+        vmt: shadowed,
+        health: 100,
+    };
+
+    let mut shadow = unsafe { ShadowVmt::new(&mut object, 2, 2) };
+    assert_eq!((shadow.prefix(), shadow.len()), (2, 2));
+    assert!(!std::ptr::eq(object.vmt, shadowed));
+    // RTTI is copied along with the entries
+    let rtti = unsafe {
+        (object.vmt as *const usize)
+            .sub(2)
+            .cast::<[usize; 2]>()
+            .read()
+    };
+    assert_eq!(rtti, [0, 0x1234]);
+
+    let original = unsafe { shadow.hook(CFoo::GET_HEALTH, get_health_fixed) };
+    assert_eq!(original(view(&mut object)), 100);
+    assert_eq!(view(&mut object).get_health(), 1337);
+    assert_eq!(view(&mut other).get_health(), 100);
+    assert_eq!(
+        shadow.original(CFoo::GET_HEALTH) as usize,
+        original as usize
+    );
+
+    shadow.unhook(CFoo::GET_HEALTH);
+    assert_eq!(view(&mut object).get_health(), 100);
+    unsafe { shadow.hook(CFoo::GET_HEALTH, get_health_fixed) };
+
+    drop(shadow);
+    assert!(std::ptr::eq(object.vmt, shadowed));
+    assert_eq!(view(&mut object).get_health(), 100);
+}